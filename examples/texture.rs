@@ -8,7 +8,8 @@ use bytemuck::cast_slice;
 use core::mem::size_of;
 use ezgl::{
   BlendEquationSeparate, BlendFuncSeparate, BufferTarget::*, BufferUsageHint::*,
-  DrawMode, EzGl, MagFilter, MinFilter, TextureTarget::*, TextureWrap,
+  DrawMode, EzGl, MagFilter, MinFilter, ProgramBuildError, ProgramBuilder,
+  ShaderPreprocessor, ShaderType, TextureTarget::*, TextureWrap,
 };
 use imagine::{image::Bitmap, pixel_formats::RGBA8888};
 use pixel_formats::{r32g32b32a32_Sfloat, r8g8b8a8_Srgb};
@@ -22,13 +23,6 @@ macro_rules! check {
 
 const USE_GLES: bool = cfg!(target_arch = "aarch64") || cfg!(target_arch = "arm");
 
-const GL_SHADER_HEADER: &str = "#version 410
-";
-
-const GLES_SHADER_HEADER: &str = "#version 310 es
-precision mediump float;
-";
-
 const VERTEX_SRC: &str = "
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec2 aTexCoord;
@@ -169,10 +163,24 @@ fn main() {
   gl.enable_vertex_attrib_array(1);
   gl.vertex_attrib_f32_pointer::<[f32; 2]>(1, size_of::<Vertex>(), size_of::<[f32; 3]>());
 
-  let shader_header = if USE_GLES { GLES_SHADER_HEADER } else { GL_SHADER_HEADER };
-  let vertex_src = format!("{shader_header}\n{VERTEX_SRC}");
-  let fragment_src = format!("{shader_header}\n{FRAGMENT_SRC}");
-  let program = gl.create_vertex_fragment_program(&vertex_src, &fragment_src).unwrap();
+  let preprocessor = ShaderPreprocessor::new(gl.get_shader_target().unwrap());
+  let vertex = preprocessor.process("vertex", VERTEX_SRC).unwrap();
+  let fragment = preprocessor.process("fragment", FRAGMENT_SRC).unwrap();
+  let program = match ProgramBuilder::new()
+    .stage(ShaderType::VertexShader, &vertex.source)
+    .stage(ShaderType::FragmentShader, &fragment.source)
+    .build(&gl)
+  {
+    Ok(program) => program,
+    Err(ProgramBuildError::Compile { stage, log }) => {
+      let processed = if stage == ShaderType::VertexShader { &vertex } else { &fragment };
+      for diagnostic in processed.diagnostics(&log) {
+        eprint!("{diagnostic}");
+      }
+      panic!("{stage:?} failed to compile");
+    }
+    Err(other) => panic!("{other}"),
+  };
   gl.use_program(&program);
 
   let model_loc = gl.get_uniform_location(&program, "model").unwrap();
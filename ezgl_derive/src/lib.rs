@@ -0,0 +1,207 @@
+//! Derive macros for the [`ezgl`](https://docs.rs/ezgl) crate.
+//!
+//! You should generally use these through `ezgl`'s `derive` feature rather
+//! than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+  parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, LitInt, Member,
+};
+
+/// Derives `ezgl::Vertex` for a struct.
+///
+/// Each field becomes one attribute, with the field's type picking the
+/// attribute's component type and count. By default the attribute locations
+/// are the field indexes, in order.
+///
+/// Fields are float attributes by default.
+///
+/// Field attributes:
+/// * `#[vertex(location = N)]` sets the attribute location of the field.
+/// * `#[vertex(integer)]` makes the field an integer attribute (`ivecN` or
+///   `uvecN` in the shader).
+/// * `#[vertex(double)]` makes the field a double attribute (`dvecN` in the
+///   shader).
+/// * `#[vertex(skip)]` doesn't make an attribute for the field (eg: padding).
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  derive_vertex_impl(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn derive_vertex_impl(input: DeriveInput) -> Result<TokenStream2, Error> {
+  let fields = match &input.data {
+    Data::Struct(s) => &s.fields,
+    _ => {
+      return Err(Error::new(input.span(), "`Vertex` can only be derived for structs"))
+    }
+  };
+  let mut attributes = Vec::new();
+  for (index, field) in fields.iter().enumerate() {
+    let mut location = index as u32;
+    let mut skip = false;
+    let mut kind = quote!(float);
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("vertex")) {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("location") {
+          location = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+          Ok(())
+        } else if meta.path.is_ident("integer") {
+          kind = quote!(integer);
+          Ok(())
+        } else if meta.path.is_ident("double") {
+          kind = quote!(double);
+          Ok(())
+        } else if meta.path.is_ident("skip") {
+          skip = true;
+          Ok(())
+        } else {
+          Err(meta.error("unknown `vertex` attribute"))
+        }
+      })?;
+    }
+    if skip {
+      continue;
+    }
+    let member = match &field.ident {
+      Some(ident) => Member::Named(ident.clone()),
+      None => Member::Unnamed(index.into()),
+    };
+    let ty = &field.ty;
+    attributes.push(quote! {
+      ::ezgl::VertexAttribute::#kind::<#ty>(#location, ::core::mem::offset_of!(Self, #member))
+    });
+  }
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  Ok(quote! {
+    unsafe impl #impl_generics ::ezgl::Vertex for #name #ty_generics #where_clause {
+      const ATTRIBUTES: &'static [::ezgl::VertexAttribute] = &[#(#attributes),*];
+    }
+  })
+}
+
+/// Derives `ezgl::Std140` for a struct.
+///
+/// Every field must also be `Std140`, and the struct is laid out with the
+/// `std140` rules for structs: each field is placed at the next multiple of
+/// its alignment, and the struct's alignment is rounded up to 16.
+#[proc_macro_derive(Std140)]
+pub fn derive_std140(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  derive_block_layout_impl(
+    input,
+    BlockLayout {
+      trait_name: quote!(Std140),
+      align: quote!(STD140_ALIGN),
+      size: quote!(STD140_SIZE),
+      write: quote!(write_std140),
+      struct_layout: quote!(std140_struct_layout),
+      read: None,
+    },
+  )
+  .unwrap_or_else(Error::into_compile_error)
+  .into()
+}
+
+/// Derives `ezgl::Std430` for a struct.
+///
+/// Every field must also be `Std430`, and the struct is laid out with the
+/// `std430` rules for structs: each field is placed at the next multiple of
+/// its alignment, and the struct's alignment is that of its most aligned
+/// field.
+#[proc_macro_derive(Std430)]
+pub fn derive_std430(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  derive_block_layout_impl(
+    input,
+    BlockLayout {
+      trait_name: quote!(Std430),
+      align: quote!(STD430_ALIGN),
+      size: quote!(STD430_SIZE),
+      write: quote!(write_std430),
+      struct_layout: quote!(std430_struct_layout),
+      read: Some(quote!(read_std430)),
+    },
+  )
+  .unwrap_or_else(Error::into_compile_error)
+  .into()
+}
+
+/// The names that make up one of the buffer block layout traits.
+struct BlockLayout {
+  trait_name: TokenStream2,
+  align: TokenStream2,
+  size: TokenStream2,
+  write: TokenStream2,
+  struct_layout: TokenStream2,
+  read: Option<TokenStream2>,
+}
+
+fn derive_block_layout_impl(
+  input: DeriveInput, layout: BlockLayout,
+) -> Result<TokenStream2, Error> {
+  let BlockLayout { trait_name, align, size, write, struct_layout, read } = layout;
+  let fields = match &input.data {
+    Data::Struct(s) => &s.fields,
+    _ => {
+      return Err(Error::new(
+        input.span(),
+        format!("`{trait_name}` can only be derived for structs"),
+      ))
+    }
+  };
+  let tys: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+  let members: Vec<_> = fields
+    .iter()
+    .enumerate()
+    .map(|(index, field)| match &field.ident {
+      Some(ident) => Member::Named(ident.clone()),
+      None => Member::Unnamed(index.into()),
+    })
+    .collect();
+  let read_fn = read.map(|read| {
+    let bindings: Vec<_> =
+      (0..tys.len()).map(|i| quote::format_ident!("field_{}", i)).collect();
+    quote! {
+      #[inline]
+      fn #read(bytes: &[u8]) -> Self {
+        let mut offset = 0;
+        #(
+          offset = ::ezgl::layout_align_up(offset, <#tys as ::ezgl::#trait_name>::#align);
+          let end = offset + <#tys as ::ezgl::#trait_name>::#size;
+          let #bindings = <#tys as ::ezgl::#trait_name>::#read(&bytes[offset..end]);
+          offset = end;
+        )*
+        let _ = offset;
+        Self { #(#members: #bindings),* }
+      }
+    }
+  });
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::ezgl::#trait_name for #name #ty_generics #where_clause {
+      const #align: usize = ::ezgl::#struct_layout(&[
+        #((<#tys as ::ezgl::#trait_name>::#align, <#tys as ::ezgl::#trait_name>::#size)),*
+      ]).0;
+      const #size: usize = ::ezgl::#struct_layout(&[
+        #((<#tys as ::ezgl::#trait_name>::#align, <#tys as ::ezgl::#trait_name>::#size)),*
+      ]).1;
+      #[inline]
+      fn #write(&self, out: &mut [u8]) {
+        let mut offset = 0;
+        #(
+          offset = ::ezgl::layout_align_up(offset, <#tys as ::ezgl::#trait_name>::#align);
+          let end = offset + <#tys as ::ezgl::#trait_name>::#size;
+          ::ezgl::#trait_name::#write(&self.#members, &mut out[offset..end]);
+          offset = end;
+        )*
+        let _ = offset;
+      }
+      #read_fn
+    }
+  })
+}
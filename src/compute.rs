@@ -0,0 +1,211 @@
+use super::*;
+
+/// Barrier bits for [`memory_barrier`](EzGl::memory_barrier).
+///
+/// Each bit names how the data written by shaders (through shader storage
+/// buffers, images, or atomic counters) will be *read* after the barrier.
+/// Combine flags with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct MemoryBarrier(GLbitfield);
+impl MemoryBarrier {
+  /// Vertex data sourced from buffers.
+  pub const VERTEX_ATTRIB_ARRAY: Self = Self(GL_VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+  /// Element data sourced from buffers.
+  pub const ELEMENT_ARRAY: Self = Self(GL_ELEMENT_ARRAY_BARRIER_BIT);
+  /// Uniform blocks sourced from buffers.
+  pub const UNIFORM: Self = Self(GL_UNIFORM_BARRIER_BIT);
+  /// Texture fetches, including from buffer textures.
+  pub const TEXTURE_FETCH: Self = Self(GL_TEXTURE_FETCH_BARRIER_BIT);
+  /// Image loads, stores, and atomics.
+  pub const SHADER_IMAGE_ACCESS: Self = Self(GL_SHADER_IMAGE_ACCESS_BARRIER_BIT);
+  /// Indirect draw and dispatch commands sourced from buffers.
+  pub const COMMAND: Self = Self(GL_COMMAND_BARRIER_BIT);
+  /// Pixel pack and unpack operations with buffers.
+  pub const PIXEL_BUFFER: Self = Self(GL_PIXEL_BUFFER_BARRIER_BIT);
+  /// Texture reads and writes with calls such as `glTexSubImage2D`.
+  pub const TEXTURE_UPDATE: Self = Self(GL_TEXTURE_UPDATE_BARRIER_BIT);
+  /// Buffer reads and writes with calls such as
+  /// [`get_buffer_sub_data`](EzGl::get_buffer_sub_data), and buffer mapping.
+  pub const BUFFER_UPDATE: Self = Self(GL_BUFFER_UPDATE_BARRIER_BIT);
+  /// Client access to persistently mapped buffers.
+  pub const CLIENT_MAPPED_BUFFER: Self = Self(GL_CLIENT_MAPPED_BUFFER_BARRIER_BIT);
+  /// Query results written to buffers.
+  pub const QUERY_BUFFER: Self = Self(GL_QUERY_BUFFER_BARRIER_BIT);
+  /// Framebuffer reads and writes.
+  pub const FRAMEBUFFER: Self = Self(GL_FRAMEBUFFER_BARRIER_BIT);
+  /// Transform feedback writes to buffers.
+  pub const TRANSFORM_FEEDBACK: Self = Self(GL_TRANSFORM_FEEDBACK_BARRIER_BIT);
+  /// Atomic counters sourced from buffers.
+  pub const ATOMIC_COUNTER: Self = Self(GL_ATOMIC_COUNTER_BARRIER_BIT);
+  /// Shader storage blocks.
+  pub const SHADER_STORAGE: Self = Self(GL_SHADER_STORAGE_BARRIER_BIT);
+  /// Every kind of access.
+  pub const ALL: Self = Self(GL_ALL_BARRIER_BITS);
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.0 & other.0) == other.0
+  }
+}
+impl core::ops::BitOr for MemoryBarrier {
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    Self(self.0 | rhs.0)
+  }
+}
+impl core::ops::BitOrAssign for MemoryBarrier {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    self.0 |= rhs.0
+  }
+}
+
+/// The compute dispatch limits of a context, from
+/// [`get_compute_limits`](EzGl::get_compute_limits).
+///
+/// These never change for a context, so query them once and keep them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComputeLimits {
+  /// The maximum number of work groups in each dimension of a dispatch.
+  pub max_work_group_count: [u32; 3],
+  /// The maximum local size of a work group in each dimension.
+  pub max_work_group_size: [u32; 3],
+  /// The maximum total number of invocations in a work group.
+  pub max_work_group_invocations: u32,
+}
+
+impl EzGl {
+  /// Compiles and links a program with only a compute shader.
+  ///
+  /// Requires GL 4.3 or GLES 3.1.
+  #[inline]
+  pub fn create_compute_program(&self, src: &str) -> Result<ProgramObject, Box<str>> {
+    ProgramBuilder::new()
+      .stage(ShaderType::ComputeShader, src)
+      .build(self)
+      .map_err(ProgramBuildError::into_log)
+  }
+
+  /// Queries all of the [`ComputeLimits`] at once.
+  #[inline]
+  pub fn get_compute_limits(&self) -> ComputeLimits {
+    ComputeLimits {
+      max_work_group_count: self.get_max_compute_work_group_count(),
+      max_work_group_size: self.get_max_compute_work_group_size(),
+      max_work_group_invocations: self.get_max_compute_work_group_invocations(),
+    }
+  }
+  /// The maximum number of work groups that can be dispatched in each
+  /// dimension.
+  #[inline]
+  pub fn get_max_compute_work_group_count(&self) -> [u32; 3] {
+    self.get_indexed_u32x3(GL_MAX_COMPUTE_WORK_GROUP_COUNT)
+  }
+  /// The maximum local size of a work group in each dimension.
+  #[inline]
+  pub fn get_max_compute_work_group_size(&self) -> [u32; 3] {
+    self.get_indexed_u32x3(GL_MAX_COMPUTE_WORK_GROUP_SIZE)
+  }
+  /// The maximum total number of invocations in a work group (the product of
+  /// the local size).
+  #[inline]
+  pub fn get_max_compute_work_group_invocations(&self) -> u32 {
+    let mut out = 0;
+    unsafe { self.GetIntegerv(GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS, &mut out) };
+    out.try_into().unwrap()
+  }
+  fn get_indexed_u32x3(&self, pname: GLenum) -> [u32; 3] {
+    let mut out = [0; 3];
+    for (i, o) in out.iter_mut().enumerate() {
+      unsafe { self.GetIntegeri_v(pname, i as GLuint, o) };
+    }
+    out.map(|i| i.try_into().unwrap())
+  }
+
+  /// The local size of a linked compute program, as declared in the shader
+  /// with `layout(local_size_x = X, local_size_y = Y, local_size_z = Z) in;`.
+  #[inline]
+  pub fn get_compute_work_group_size(&self, program: &ProgramObject) -> [u32; 3] {
+    let mut out = [0; 3];
+    unsafe {
+      self.GetProgramiv(program.0.get(), GL_COMPUTE_WORK_GROUP_SIZE, out.as_mut_ptr())
+    };
+    out.map(|i| i.try_into().unwrap())
+  }
+
+  /// Runs the current program's compute shader, with `x * y * z` work groups.
+  ///
+  /// * `limits` should come from
+  ///   [`get_compute_limits`](Self::get_compute_limits) on this context.
+  ///
+  /// ## Failure
+  /// * Any count is over the limit in
+  ///   [`max_work_group_count`](ComputeLimits::max_work_group_count).
+  ///
+  /// ## Safety
+  /// * The shader's reads and writes through bound buffers and images must not
+  ///   go out of bounds, or you must have configured the context for robust
+  ///   buffer access.
+  #[inline]
+  pub unsafe fn dispatch_compute(
+    &self, limits: &ComputeLimits, x: u32, y: u32, z: u32,
+  ) -> Result<(), ()> {
+    let [max_x, max_y, max_z] = limits.max_work_group_count;
+    if x > max_x || y > max_y || z > max_z {
+      return Err(());
+    }
+    self.DispatchCompute(x, y, z);
+    Ok(())
+  }
+
+  /// Runs the current program's compute shader, with the work group counts
+  /// read from the
+  /// [`DispatchIndirectBuffer`](BufferTarget::DispatchIndirectBuffer).
+  ///
+  /// * `offset` is the byte offset of a [`DispatchIndirectCommand`] within the
+  ///   indirect buffer. It must be a multiple of 4.
+  ///
+  /// ## Panics
+  /// * `assert!(offset.is_multiple_of(4));`
+  ///
+  /// ## Safety
+  /// * Same as [`dispatch_compute`](Self::dispatch_compute).
+  /// * The command must be within the indirect buffer.
+  /// * The counts of the command must be within the limits from
+  ///   [`get_max_compute_work_group_count`](Self::get_max_compute_work_group_count).
+  #[inline]
+  pub unsafe fn dispatch_compute_indirect(&self, offset: usize) {
+    assert!(offset.is_multiple_of(4));
+    self.DispatchComputeIndirect(offset.try_into().unwrap())
+  }
+
+  /// Orders memory accesses made by shaders before this call against the
+  /// accesses of the given kinds made after it.
+  ///
+  /// Khronos: [glMemoryBarrier](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glMemoryBarrier.xhtml)
+  #[inline]
+  pub fn memory_barrier(&self, barriers: MemoryBarrier) {
+    unsafe { self.MemoryBarrier(barriers.0) }
+  }
+
+  /// As [`memory_barrier`](Self::memory_barrier), but only orders accesses by
+  /// fragment shaders within the same framebuffer region.
+  ///
+  /// This is cheaper for fragment shaders that only read image or buffer data
+  /// written by earlier fragments at the same location. Only these bits are
+  /// allowed: [`ATOMIC_COUNTER`](MemoryBarrier::ATOMIC_COUNTER),
+  /// [`FRAMEBUFFER`](MemoryBarrier::FRAMEBUFFER),
+  /// [`SHADER_IMAGE_ACCESS`](MemoryBarrier::SHADER_IMAGE_ACCESS),
+  /// [`SHADER_STORAGE`](MemoryBarrier::SHADER_STORAGE),
+  /// [`TEXTURE_FETCH`](MemoryBarrier::TEXTURE_FETCH),
+  /// [`UNIFORM`](MemoryBarrier::UNIFORM), or
+  /// [`ALL`](MemoryBarrier::ALL).
+  ///
+  /// Requires GL 4.5 or GLES 3.1.
+  #[inline]
+  pub fn memory_barrier_by_region(&self, barriers: MemoryBarrier) {
+    unsafe { self.MemoryBarrierByRegion(barriers.0) }
+  }
+}
@@ -0,0 +1,281 @@
+use super::*;
+
+/// How a shader may access an image unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ImageAccess {
+  ReadOnly = GL_READ_ONLY,
+  WriteOnly = GL_WRITE_ONLY,
+  ReadWrite = GL_READ_WRITE,
+}
+
+macro_rules! image_formats {
+  ($($v:ident = $c:ident, $size:literal, $class:ident;)*) => {
+    /// The formats that a texture can be bound to an image unit with.
+    ///
+    /// These match the format layout qualifiers in GLSL, eg: `Rgba8` is
+    /// `layout(rgba8)`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u32)]
+    pub enum ImageFormat {
+      $($v = $c,)*
+    }
+    impl ImageFormat {
+      /// Converts a sized internal format, if it's usable as an image format.
+      #[inline]
+      #[must_use]
+      pub const fn from_gl_enum(format: GLenum) -> Option<Self> {
+        match format {
+          $($c => Some(Self::$v),)*
+          _ => None,
+        }
+      }
+      /// The size of one texel, in bytes.
+      #[inline]
+      #[must_use]
+      pub const fn texel_size(self) -> usize {
+        match self {
+          $(Self::$v => $size,)*
+        }
+      }
+      const fn class(self) -> FormatClass {
+        match self {
+          $(Self::$v => FormatClass::$class,)*
+        }
+      }
+    }
+  };
+}
+
+/// The "Class" column of the image format compatibility table (GL 4.6 table
+/// 8.27): the number of components and the bits in each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormatClass {
+  X4x32,
+  X4x16,
+  X4x8,
+  X2x32,
+  X2x16,
+  X2x8,
+  X1x32,
+  X1x16,
+  X1x8,
+  R11G11B10,
+  Rgb10A2,
+}
+
+image_formats! {
+  Rgba32f = GL_RGBA32F, 16, X4x32;
+  Rgba16f = GL_RGBA16F, 8, X4x16;
+  Rg32f = GL_RG32F, 8, X2x32;
+  Rg16f = GL_RG16F, 4, X2x16;
+  R11fG11fB10f = GL_R11F_G11F_B10F, 4, R11G11B10;
+  R32f = GL_R32F, 4, X1x32;
+  R16f = GL_R16F, 2, X1x16;
+  Rgba32ui = GL_RGBA32UI, 16, X4x32;
+  Rgba16ui = GL_RGBA16UI, 8, X4x16;
+  Rgb10A2ui = GL_RGB10_A2UI, 4, Rgb10A2;
+  Rgba8ui = GL_RGBA8UI, 4, X4x8;
+  Rg32ui = GL_RG32UI, 8, X2x32;
+  Rg16ui = GL_RG16UI, 4, X2x16;
+  Rg8ui = GL_RG8UI, 2, X2x8;
+  R32ui = GL_R32UI, 4, X1x32;
+  R16ui = GL_R16UI, 2, X1x16;
+  R8ui = GL_R8UI, 1, X1x8;
+  Rgba32i = GL_RGBA32I, 16, X4x32;
+  Rgba16i = GL_RGBA16I, 8, X4x16;
+  Rgba8i = GL_RGBA8I, 4, X4x8;
+  Rg32i = GL_RG32I, 8, X2x32;
+  Rg16i = GL_RG16I, 4, X2x16;
+  Rg8i = GL_RG8I, 2, X2x8;
+  R32i = GL_R32I, 4, X1x32;
+  R16i = GL_R16I, 2, X1x16;
+  R8i = GL_R8I, 1, X1x8;
+  Rgba16 = GL_RGBA16, 8, X4x16;
+  Rgb10A2 = GL_RGB10_A2, 4, Rgb10A2;
+  Rgba8 = GL_RGBA8, 4, X4x8;
+  Rg16 = GL_RG16, 4, X2x16;
+  Rg8 = GL_RG8, 2, X2x8;
+  R16 = GL_R16, 2, X1x16;
+  R8 = GL_R8, 1, X1x8;
+  Rgba16Snorm = GL_RGBA16_SNORM, 8, X4x16;
+  Rgba8Snorm = GL_RGBA8_SNORM, 4, X4x8;
+  Rg16Snorm = GL_RG16_SNORM, 4, X2x16;
+  Rg8Snorm = GL_RG8_SNORM, 2, X2x8;
+  R16Snorm = GL_R16_SNORM, 2, X1x16;
+  R8Snorm = GL_R8_SNORM, 1, X1x8;
+}
+
+impl ImageFormat {
+  /// If a texture with this internal format can be bound to an image unit
+  /// with `format`.
+  ///
+  /// Formats are compatible when they're the same, or when they match in size
+  /// (`by_class == false`) or in class (`by_class == true`). Which of those a
+  /// texture uses is its `GL_IMAGE_FORMAT_COMPATIBILITY_TYPE`.
+  #[inline]
+  #[must_use]
+  pub const fn is_compatible_with(self, format: Self, by_class: bool) -> bool {
+    if by_class {
+      self.class() as u8 == format.class() as u8
+    } else {
+      self.texel_size() == format.texel_size()
+    }
+  }
+}
+
+/// The image unit limits of a context, from
+/// [`get_image_limits`](EzGl::get_image_limits).
+///
+/// These never change for a context, so query them once and keep them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImageLimits {
+  /// The number of image units available.
+  pub max_image_units: u32,
+  /// If textures say whether their formats are compatible by size or by
+  /// class. This is desktop GL only, GLES always matches by size.
+  pub has_format_compatibility_type: bool,
+}
+
+impl EzGl {
+  /// Queries all of the [`ImageLimits`] at once.
+  #[inline]
+  pub fn get_image_limits(&self) -> ImageLimits {
+    ImageLimits {
+      max_image_units: self.get_max_image_units(),
+      has_format_compatibility_type: !self.get_version().starts_with("OpenGL ES"),
+    }
+  }
+  /// The number of image units available.
+  #[inline]
+  pub fn get_max_image_units(&self) -> u32 {
+    let mut out = 0;
+    unsafe { self.GetIntegerv(GL_MAX_IMAGE_UNITS, &mut out) };
+    out.try_into().unwrap()
+  }
+
+  /// Binds a level of a texture to an image unit, for image loads and stores
+  /// in shaders.
+  ///
+  /// * `limits` should come from
+  ///   [`get_image_limits`](Self::get_image_limits) on this context.
+  /// * `target` is the target that `texture` was created with.
+  /// * `layer` picks a single layer of an array, cube map, or 3D texture. With
+  ///   `None` all layers are bound.
+  /// * `format` is how shaders see the texels, and it must match the format
+  ///   qualifier of the image uniform.
+  ///
+  /// The texture's internal format at `level` is checked against `format`
+  /// with [`ImageFormat::is_compatible_with`]. When
+  /// `glGetTextureLevelParameteriv` (GL 4.5) isn't loaded, the check binds
+  /// `texture` to `target` on the active texture unit and leaves it bound.
+  ///
+  /// Writes through the image unit are only visible to later reads after a
+  /// [`memory_barrier`](Self::memory_barrier) with the bits for those reads,
+  /// such as [`SHADER_IMAGE_ACCESS`](MemoryBarrier::SHADER_IMAGE_ACCESS) or
+  /// [`TEXTURE_FETCH`](MemoryBarrier::TEXTURE_FETCH).
+  ///
+  /// ## Failure
+  /// * `unit` is not below `limits.max_image_units`.
+  /// * The texture's internal format at `level` is not an [`ImageFormat`], or
+  ///   is not compatible with `format`.
+  #[inline]
+  #[allow(clippy::too_many_arguments)]
+  pub fn bind_image_texture(
+    &self, limits: &ImageLimits, unit: u32, texture: &TextureObject,
+    target: TextureTarget, level: u32, layer: Option<u32>, access: ImageAccess,
+    format: ImageFormat,
+  ) -> Result<(), ()> {
+    if unit >= limits.max_image_units {
+      return Err(());
+    }
+    let gl_level: GLint = level.try_into().map_err(|_| ())?;
+    let mut internal_format = 0;
+    let mut compatibility_type = GL_IMAGE_FORMAT_COMPATIBILITY_BY_SIZE as GLint;
+    if self.has_loaded().GetTextureLevelParameteriv() {
+      unsafe {
+        self.GetTextureLevelParameteriv(
+          texture.0.get(),
+          gl_level,
+          GL_TEXTURE_INTERNAL_FORMAT,
+          &mut internal_format,
+        );
+        if limits.has_format_compatibility_type {
+          self.GetTextureParameteriv(
+            texture.0.get(),
+            GL_IMAGE_FORMAT_COMPATIBILITY_TYPE,
+            &mut compatibility_type,
+          );
+        }
+      }
+    } else {
+      self.bind_texture(target, texture);
+      // Level queries on a cube map have to name a face, and all faces of a
+      // complete cube map have the same format.
+      let level_target = match target {
+        TextureTarget::TextureCubeMap => GL_TEXTURE_CUBE_MAP_POSITIVE_X,
+        other => other as GLenum,
+      };
+      unsafe {
+        self.GetTexLevelParameteriv(
+          level_target,
+          gl_level,
+          GL_TEXTURE_INTERNAL_FORMAT,
+          &mut internal_format,
+        );
+        if limits.has_format_compatibility_type {
+          self.GetTexParameteriv(
+            target as GLenum,
+            GL_IMAGE_FORMAT_COMPATIBILITY_TYPE,
+            &mut compatibility_type,
+          );
+        }
+      }
+    }
+    let by_class = compatibility_type as GLenum == GL_IMAGE_FORMAT_COMPATIBILITY_BY_CLASS;
+    let texture_format = ImageFormat::from_gl_enum(internal_format as GLenum).ok_or(())?;
+    if !texture_format.is_compatible_with(format, by_class) {
+      return Err(());
+    }
+    unsafe {
+      self.BindImageTexture(
+        unit,
+        texture.0.get(),
+        gl_level,
+        if layer.is_none() { GLboolean::TRUE } else { GLboolean::FALSE },
+        layer.unwrap_or(0).try_into().unwrap(),
+        access as GLenum,
+        format as GLenum,
+      )
+    };
+    Ok(())
+  }
+  #[inline]
+  pub fn clear_image_texture_binding(&self, unit: u32) {
+    unsafe { self.BindImageTexture(unit, 0, 0, GLboolean::FALSE, 0, GL_READ_ONLY, GL_R8) }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn same_size_formats_are_compatible_by_size() {
+    assert!(ImageFormat::Rgba8.is_compatible_with(ImageFormat::R32ui, false));
+    assert!(ImageFormat::Rgba8.is_compatible_with(ImageFormat::Rgba8Snorm, false));
+    assert!(ImageFormat::R11fG11fB10f.is_compatible_with(ImageFormat::R32f, false));
+    assert!(!ImageFormat::Rgba8.is_compatible_with(ImageFormat::Rg8, false));
+    assert!(!ImageFormat::Rgba16f.is_compatible_with(ImageFormat::Rgba32f, false));
+  }
+
+  #[test]
+  fn same_class_formats_are_compatible_by_class() {
+    assert!(ImageFormat::Rgba8.is_compatible_with(ImageFormat::Rgba8ui, true));
+    assert!(ImageFormat::Rgba16f.is_compatible_with(ImageFormat::Rgba16Snorm, true));
+    assert!(ImageFormat::Rgb10A2.is_compatible_with(ImageFormat::Rgb10A2ui, true));
+    assert!(!ImageFormat::Rgba8.is_compatible_with(ImageFormat::R32ui, true));
+    assert!(!ImageFormat::Rgba16f.is_compatible_with(ImageFormat::Rg32f, true));
+    assert!(!ImageFormat::R11fG11fB10f.is_compatible_with(ImageFormat::R32f, true));
+  }
+}
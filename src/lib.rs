@@ -6,10 +6,15 @@
 
 pub use gl_constants;
 
+// Lets the derive macros' `::ezgl::` paths work in this crate's own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as ezgl;
+
 use core::{
+  mem::size_of,
   num::NonZeroU32,
-  ops::{Deref, DerefMut, Range},
-  ptr::null,
+  ops::{Deref, DerefMut, Range, RangeInclusive},
+  ptr::{null, null_mut, NonNull},
   slice::from_raw_parts as slice_from_raw_parts,
 };
 use gl_constants::*;
@@ -19,6 +24,48 @@ use pixel_formats::{
   r32g32b32a32_Sfloat, r8g8b8_Srgb, r8g8b8_Unorm, r8g8b8a8_Srgb, r8g8b8a8_Unorm,
 };
 
+mod compute;
+pub use compute::*;
+
+mod image;
+pub use image::*;
+
+mod program_builder;
+pub use program_builder::*;
+
+mod program_cache;
+pub use program_cache::*;
+
+mod program_introspection;
+pub use program_introspection::*;
+
+mod program_pipeline;
+pub use program_pipeline::*;
+
+#[cfg(all(test, feature = "derive"))]
+mod spec_example;
+
+mod shader_log;
+pub use shader_log::*;
+
+mod shader_source;
+pub use shader_source::*;
+
+mod std140;
+pub use std140::*;
+
+mod std430;
+pub use std430::*;
+
+mod stream_buffer;
+pub use stream_buffer::*;
+
+mod tracked_vertex_array;
+pub use tracked_vertex_array::*;
+
+mod uniform;
+pub use uniform::*;
+
 unsafe extern "system" fn stderr_debug_message_callback(
   source: GLenum, ty: GLenum, id: GLuint, severity: GLenum, length: GLsizei,
   message: *const GLchar, _user_data: *const c_void,
@@ -67,14 +114,12 @@ impl EzGl {
 impl Deref for EzGl {
   type Target = GlFns;
   #[inline]
-  #[must_use]
   fn deref(&self) -> &Self::Target {
     &self.0
   }
 }
 impl DerefMut for EzGl {
   #[inline]
-  #[must_use]
   fn deref_mut(&mut self) -> &mut Self::Target {
     &mut self.0
   }
@@ -127,6 +172,51 @@ impl EzGl {
   pub fn bind_buffer(&self, target: BufferTarget, buffer: &BufferObject) {
     unsafe { self.BindBuffer(target as GLenum, buffer.0.get()) };
   }
+  #[inline]
+  pub fn clear_buffer_binding(&self, target: BufferTarget) {
+    unsafe { self.BindBuffer(target as GLenum, 0) };
+  }
+  /// Binds a buffer to an indexed binding point of `target` (and also to
+  /// `target` itself).
+  ///
+  /// The target must be an indexed target: one of
+  /// [`UniformBuffer`](BufferTarget::UniformBuffer),
+  /// [`ShaderStorageBuffer`](BufferTarget::ShaderStorageBuffer),
+  /// [`AtomicCounterBuffer`](BufferTarget::AtomicCounterBuffer), or
+  /// [`TransformFeedbackBuffer`](BufferTarget::TransformFeedbackBuffer).
+  #[inline]
+  pub fn bind_buffer_base(
+    &self, target: BufferTarget, index: GLuint, buffer: &BufferObject,
+  ) {
+    unsafe { self.BindBufferBase(target as GLenum, index, buffer.0.get()) };
+  }
+  /// As [`bind_buffer_base`](Self::bind_buffer_base), but binds only a byte
+  /// range of the buffer.
+  ///
+  /// The start of the range must be a multiple of the offset alignment of the
+  /// target, see
+  /// [`get_uniform_buffer_offset_alignment`](Self::get_uniform_buffer_offset_alignment)
+  /// and
+  /// [`get_shader_storage_buffer_offset_alignment`](Self::get_shader_storage_buffer_offset_alignment).
+  #[inline]
+  pub fn bind_buffer_range(
+    &self, target: BufferTarget, index: GLuint, buffer: &BufferObject,
+    range: Range<usize>,
+  ) {
+    unsafe {
+      self.BindBufferRange(
+        target as GLenum,
+        index,
+        buffer.0.get(),
+        range.start.try_into().unwrap(),
+        (range.end - range.start).try_into().unwrap(),
+      )
+    };
+  }
+  #[inline]
+  pub fn delete_buffer(&self, buffer: BufferObject) {
+    unsafe { self.DeleteBuffers(1, &buffer.0.get()) };
+  }
   /// Allocate new storage for the buffer bound to `target` and copy this data
   /// into it.
   ///
@@ -142,6 +232,58 @@ impl EzGl {
       )
     }
   }
+  /// Allocate new storage of `len` bytes for the buffer bound to `target`,
+  /// without initializing it.
+  ///
+  /// This is also how you "orphan" a streaming buffer: the old storage stays
+  /// alive for any draws still using it, and you get fresh storage to write
+  /// into with [`buffer_sub_data`](Self::buffer_sub_data).
+  #[inline]
+  pub fn buffer_data_uninit(
+    &self, target: BufferTarget, len: usize, usage: BufferUsageHint,
+  ) {
+    unsafe {
+      self.BufferData(target as GLenum, len.try_into().unwrap(), null(), usage as GLenum)
+    }
+  }
+  /// Overwrite part of the existing storage of the buffer bound to `target`,
+  /// starting at `offset` bytes.
+  ///
+  /// This doesn't reallocate, so the range written must be within the size of
+  /// the buffer's current storage.
+  ///
+  /// Khronos: [glBufferSubData](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glBufferSubData.xhtml)
+  #[inline]
+  pub fn buffer_sub_data(&self, target: BufferTarget, offset: usize, data: &[u8]) {
+    unsafe {
+      self.BufferSubData(
+        target as GLenum,
+        offset.try_into().unwrap(),
+        data.len().try_into().unwrap(),
+        data.as_ptr().cast::<c_void>(),
+      )
+    }
+  }
+  /// As [`buffer_data`](Self::buffer_data), but with any [`Pod`](bytemuck::Pod)
+  /// slice.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn buffer_data_pod<T: bytemuck::Pod>(
+    &self, target: BufferTarget, data: &[T], usage: BufferUsageHint,
+  ) {
+    self.buffer_data(target, bytemuck::cast_slice(data), usage)
+  }
+  /// As [`buffer_sub_data`](Self::buffer_sub_data), but with any
+  /// [`Pod`](bytemuck::Pod) slice.
+  ///
+  /// The `offset` is still in bytes, not elements.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn buffer_sub_data_pod<T: bytemuck::Pod>(
+    &self, target: BufferTarget, offset: usize, data: &[T],
+  ) {
+    self.buffer_sub_data(target, offset, bytemuck::cast_slice(data))
+  }
   #[inline]
   pub fn create_shader(&self, shader_type: ShaderType) -> Result<ShaderObject, ()> {
     NonZeroU32::new(unsafe { self.CreateShader(shader_type as GLenum) })
@@ -194,6 +336,10 @@ impl EzGl {
     unsafe { self.AttachShader(program.0.get(), shader.0.get()) }
   }
   #[inline]
+  pub fn detach_shader(&self, program: &ProgramObject, shader: &ShaderObject) {
+    unsafe { self.DetachShader(program.0.get(), shader.0.get()) }
+  }
+  #[inline]
   pub fn link_program(&self, program: &ProgramObject) {
     unsafe { self.LinkProgram(program.0.get()) }
   }
@@ -286,15 +432,122 @@ impl EzGl {
     unsafe { self.Uniform1i(loc.0, v0) };
   }
   #[inline]
+  pub fn set_uniform_float(&self, loc: ShaderLocation, v0: f32) {
+    unsafe { self.Uniform1f(loc.0, v0) };
+  }
+  #[inline]
+  pub fn set_uniform_vec2(&self, loc: ShaderLocation, v0: f32, v1: f32) {
+    unsafe { self.Uniform2f(loc.0, v0, v1) };
+  }
+  #[inline]
+  pub fn set_uniform_vec3(&self, loc: ShaderLocation, v0: f32, v1: f32, v2: f32) {
+    unsafe { self.Uniform3f(loc.0, v0, v1, v2) };
+  }
+  #[inline]
   pub fn set_uniform_vec4(
     &self, loc: ShaderLocation, v0: f32, v1: f32, v2: f32, v3: f32,
   ) {
     unsafe { self.Uniform4f(loc.0, v0, v1, v2, v3) };
   }
   #[inline]
+  pub fn set_uniform_int(&self, loc: ShaderLocation, v0: i32) {
+    unsafe { self.Uniform1i(loc.0, v0) };
+  }
+  #[inline]
+  pub fn set_uniform_ivec2(&self, loc: ShaderLocation, v0: i32, v1: i32) {
+    unsafe { self.Uniform2i(loc.0, v0, v1) };
+  }
+  #[inline]
+  pub fn set_uniform_ivec3(&self, loc: ShaderLocation, v0: i32, v1: i32, v2: i32) {
+    unsafe { self.Uniform3i(loc.0, v0, v1, v2) };
+  }
+  #[inline]
+  pub fn set_uniform_ivec4(
+    &self, loc: ShaderLocation, v0: i32, v1: i32, v2: i32, v3: i32,
+  ) {
+    unsafe { self.Uniform4i(loc.0, v0, v1, v2, v3) };
+  }
+  #[inline]
+  pub fn set_uniform_uint(&self, loc: ShaderLocation, v0: u32) {
+    unsafe { self.Uniform1ui(loc.0, v0) };
+  }
+  #[inline]
+  pub fn set_uniform_uvec2(&self, loc: ShaderLocation, v0: u32, v1: u32) {
+    unsafe { self.Uniform2ui(loc.0, v0, v1) };
+  }
+  #[inline]
+  pub fn set_uniform_uvec3(&self, loc: ShaderLocation, v0: u32, v1: u32, v2: u32) {
+    unsafe { self.Uniform3ui(loc.0, v0, v1, v2) };
+  }
+  #[inline]
+  pub fn set_uniform_uvec4(
+    &self, loc: ShaderLocation, v0: u32, v1: u32, v2: u32, v3: u32,
+  ) {
+    unsafe { self.Uniform4ui(loc.0, v0, v1, v2, v3) };
+  }
+  #[inline]
+  pub fn set_uniform_bool(&self, loc: ShaderLocation, v0: bool) {
+    unsafe { self.Uniform1i(loc.0, v0 as GLint) };
+  }
+  #[inline]
+  pub fn set_uniform_bvec2(&self, loc: ShaderLocation, v0: bool, v1: bool) {
+    unsafe { self.Uniform2i(loc.0, v0 as GLint, v1 as GLint) };
+  }
+  #[inline]
+  pub fn set_uniform_bvec3(&self, loc: ShaderLocation, v0: bool, v1: bool, v2: bool) {
+    unsafe { self.Uniform3i(loc.0, v0 as GLint, v1 as GLint, v2 as GLint) };
+  }
+  #[inline]
+  pub fn set_uniform_bvec4(
+    &self, loc: ShaderLocation, v0: bool, v1: bool, v2: bool, v3: bool,
+  ) {
+    unsafe { self.Uniform4i(loc.0, v0 as GLint, v1 as GLint, v2 as GLint, v3 as GLint) };
+  }
+  /// Column-major, 2 columns of 2 rows.
+  #[inline]
+  pub fn set_uniform_mat2(&self, loc: ShaderLocation, mat2s: &[f32; 4]) {
+    unsafe { self.UniformMatrix2fv(loc.0, 1, GLboolean::FALSE, mat2s.as_ptr()) };
+  }
+  /// Column-major, 3 columns of 3 rows.
+  #[inline]
+  pub fn set_uniform_mat3(&self, loc: ShaderLocation, mat3s: &[f32; 9]) {
+    unsafe { self.UniformMatrix3fv(loc.0, 1, GLboolean::FALSE, mat3s.as_ptr()) };
+  }
+  /// Column-major, 4 columns of 4 rows.
+  #[inline]
   pub fn set_uniform_mat4(&self, loc: ShaderLocation, mat4s: &[f32; 16]) {
     unsafe { self.UniformMatrix4fv(loc.0, 1, GLboolean::FALSE, mat4s.as_ptr()) };
   }
+  /// Column-major, 2 columns of 3 rows.
+  #[inline]
+  pub fn set_uniform_mat2x3(&self, loc: ShaderLocation, mat2x3s: &[f32; 6]) {
+    unsafe { self.UniformMatrix2x3fv(loc.0, 1, GLboolean::FALSE, mat2x3s.as_ptr()) };
+  }
+  /// Column-major, 2 columns of 4 rows.
+  #[inline]
+  pub fn set_uniform_mat2x4(&self, loc: ShaderLocation, mat2x4s: &[f32; 8]) {
+    unsafe { self.UniformMatrix2x4fv(loc.0, 1, GLboolean::FALSE, mat2x4s.as_ptr()) };
+  }
+  /// Column-major, 3 columns of 2 rows.
+  #[inline]
+  pub fn set_uniform_mat3x2(&self, loc: ShaderLocation, mat3x2s: &[f32; 6]) {
+    unsafe { self.UniformMatrix3x2fv(loc.0, 1, GLboolean::FALSE, mat3x2s.as_ptr()) };
+  }
+  /// Column-major, 3 columns of 4 rows.
+  #[inline]
+  pub fn set_uniform_mat3x4(&self, loc: ShaderLocation, mat3x4s: &[f32; 12]) {
+    unsafe { self.UniformMatrix3x4fv(loc.0, 1, GLboolean::FALSE, mat3x4s.as_ptr()) };
+  }
+  /// Column-major, 4 columns of 2 rows.
+  #[inline]
+  pub fn set_uniform_mat4x2(&self, loc: ShaderLocation, mat4x2s: &[f32; 8]) {
+    unsafe { self.UniformMatrix4x2fv(loc.0, 1, GLboolean::FALSE, mat4x2s.as_ptr()) };
+  }
+  /// Column-major, 4 columns of 3 rows.
+  #[inline]
+  pub fn set_uniform_mat4x3(&self, loc: ShaderLocation, mat4x3s: &[f32; 12]) {
+    unsafe { self.UniformMatrix4x3fv(loc.0, 1, GLboolean::FALSE, mat4x3s.as_ptr()) };
+  }
   #[inline]
   pub fn set_texture_wrap_s(&self, target: TextureTarget, wrap: TextureWrap) {
     unsafe { self.TexParameteri(target as GLenum, GL_TEXTURE_WRAP_S, wrap as GLint) }
@@ -327,6 +580,75 @@ impl EzGl {
       self.TexParameteri(target as GLenum, GL_TEXTURE_MAG_FILTER, filter as GLint)
     }
   }
+  /// Sets where each of the four channels of a texture sample comes from.
+  ///
+  /// The array is in `[r, g, b, a]` order. For example, an `R8` texture can be
+  /// shown as white-with-alpha using `[One, One, One, Red]`.
+  ///
+  /// This sets all four channels separately (rather than using
+  /// `GL_TEXTURE_SWIZZLE_RGBA`) so that it also works on GLES.
+  #[inline]
+  pub fn set_texture_swizzle(&self, target: TextureTarget, swizzle: [Swizzle; 4]) {
+    let [r, g, b, a] = swizzle;
+    unsafe {
+      self.TexParameteri(target as GLenum, GL_TEXTURE_SWIZZLE_R, r as GLint);
+      self.TexParameteri(target as GLenum, GL_TEXTURE_SWIZZLE_G, g as GLint);
+      self.TexParameteri(target as GLenum, GL_TEXTURE_SWIZZLE_B, b as GLint);
+      self.TexParameteri(target as GLenum, GL_TEXTURE_SWIZZLE_A, a as GLint);
+    }
+  }
+  /// The lowest defined mipmap level (default 0).
+  #[inline]
+  pub fn set_texture_base_level(&self, target: TextureTarget, level: GLint) {
+    unsafe { self.TexParameteri(target as GLenum, GL_TEXTURE_BASE_LEVEL, level) }
+  }
+  /// The highest defined mipmap level (default 1000).
+  #[inline]
+  pub fn set_texture_max_level(&self, target: TextureTarget, level: GLint) {
+    unsafe { self.TexParameteri(target as GLenum, GL_TEXTURE_MAX_LEVEL, level) }
+  }
+  /// Clamps the level of detail that sampling can select.
+  ///
+  /// The default range is `-1000.0..=1000.0`.
+  #[inline]
+  pub fn set_texture_lod_range(&self, target: TextureTarget, lod: RangeInclusive<f32>) {
+    unsafe {
+      self.TexParameterf(target as GLenum, GL_TEXTURE_MIN_LOD, *lod.start());
+      self.TexParameterf(target as GLenum, GL_TEXTURE_MAX_LOD, *lod.end());
+    }
+  }
+  /// Sets if sampling a depth texture with a shadow sampler compares against
+  /// the reference value.
+  ///
+  /// * `Some(func)`: Sampling gives the result of the comparison (0.0 or 1.0).
+  /// * `None`: Sampling gives the depth value itself.
+  #[inline]
+  pub fn set_texture_compare(&self, target: TextureTarget, func: Option<CompareFunc>) {
+    unsafe {
+      if let Some(func) = func {
+        self.TexParameteri(
+          target as GLenum,
+          GL_TEXTURE_COMPARE_MODE,
+          GL_COMPARE_REF_TO_TEXTURE as GLint,
+        );
+        self.TexParameteri(target as GLenum, GL_TEXTURE_COMPARE_FUNC, func as GLint);
+      } else {
+        self.TexParameteri(target as GLenum, GL_TEXTURE_COMPARE_MODE, GL_NONE as GLint);
+      }
+    }
+  }
+  /// For depth-stencil textures, sets if sampling reads the depth or the
+  /// stencil component.
+  ///
+  /// Stencil values must be read with an unsigned integer sampler (`usampler`).
+  #[inline]
+  pub fn set_depth_stencil_texture_mode(
+    &self, target: TextureTarget, mode: DepthStencilTextureMode,
+  ) {
+    unsafe {
+      self.TexParameteri(target as GLenum, GL_DEPTH_STENCIL_TEXTURE_MODE, mode as GLint)
+    }
+  }
   #[inline]
   pub fn gen_texture(&self) -> Result<TextureObject, ()> {
     let mut obj = 0;
@@ -418,24 +740,46 @@ impl EzGl {
       Err(log)
     }
   }
+  /// Compiles and links a program with a vertex and fragment shader.
+  ///
+  /// On failure the error is the compile or link log. Use a
+  /// [`ProgramBuilder`] for other stages, or to know which stage failed.
   #[inline]
   pub fn create_vertex_fragment_program(
     &self, vertex_src: &str, fragment_src: &str,
   ) -> Result<ProgramObject, Box<str>> {
-    let v = self.create_shader_with_source(ShaderType::VertexShader, vertex_src)?;
-    let f = self.create_shader_with_source(ShaderType::FragmentShader, fragment_src)?;
-    let program = self
-      .create_program()
-      .map_err(|_| String::from("Couldn't create a new program ID.").into_boxed_str())?;
-    self.attach_shader(&program, &v);
-    self.attach_shader(&program, &f);
-    self.link_program(&program);
-    if self.get_program_link_success(&program) {
-      Ok(program)
+    ProgramBuilder::new()
+      .stage(ShaderType::VertexShader, vertex_src)
+      .stage(ShaderType::FragmentShader, fragment_src)
+      .build(self)
+      .map_err(ProgramBuildError::into_log)
+  }
+  /// The company responsible for the GL implementation.
+  #[inline]
+  pub fn get_vendor(&self) -> Box<str> {
+    self.get_string(GL_VENDOR)
+  }
+  /// The name of the renderer, usually the GPU model.
+  #[inline]
+  pub fn get_renderer(&self) -> Box<str> {
+    self.get_string(GL_RENDERER)
+  }
+  /// The GL or GLES version string, which often includes the driver version.
+  #[inline]
+  pub fn get_version(&self) -> Box<str> {
+    self.get_string(GL_VERSION)
+  }
+  #[inline]
+  pub fn get_shading_language_version(&self) -> Box<str> {
+    self.get_string(GL_SHADING_LANGUAGE_VERSION)
+  }
+  fn get_string(&self, name: GLenum) -> Box<str> {
+    let p = unsafe { self.GetString(name) };
+    if p.is_null() {
+      String::new().into_boxed_str()
     } else {
-      let log = self.get_program_info_log(&program);
-      self.delete_program(program);
-      Err(log)
+      let c_str = unsafe { core::ffi::CStr::from_ptr(p.cast()) };
+      c_str.to_string_lossy().into_owned().into_boxed_str()
     }
   }
   /// The maximum number of texture image units that the sampler in the
@@ -458,6 +802,22 @@ impl EzGl {
     unsafe { self.GetIntegerv(GL_MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut out) };
     out.try_into().unwrap()
   }
+  /// The required alignment of offsets passed to `glBindBufferRange` for
+  /// [`UniformBuffer`](BufferTarget::UniformBuffer).
+  #[inline]
+  pub fn get_uniform_buffer_offset_alignment(&self) -> usize {
+    let mut out = 0;
+    unsafe { self.GetIntegerv(GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT, &mut out) };
+    out.try_into().unwrap()
+  }
+  /// The required alignment of offsets passed to `glBindBufferRange` for
+  /// [`ShaderStorageBuffer`](BufferTarget::ShaderStorageBuffer).
+  #[inline]
+  pub fn get_shader_storage_buffer_offset_alignment(&self) -> usize {
+    let mut out = 0;
+    unsafe { self.GetIntegerv(GL_SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT, &mut out) };
+    out.try_into().unwrap()
+  }
   /// Sets the active texture unit value.
   ///
   /// This should be from 0 to one less than
@@ -539,111 +899,1948 @@ impl EzGl {
       base as *const c_void,
     )
   }
-}
-
-/// Types that can be used with a "draw elements" call.
-///
-/// ## Safety
-/// * You cannot implement this trait.
-pub unsafe trait DrawElementsType {
-  const TY: GLenum;
-}
-unsafe impl DrawElementsType for u8 {
-  const TY: GLenum = GL_UNSIGNED_BYTE;
-}
-unsafe impl DrawElementsType for u16 {
-  const TY: GLenum = GL_UNSIGNED_SHORT;
-}
-unsafe impl DrawElementsType for u32 {
-  const TY: GLenum = GL_UNSIGNED_INT;
-}
-
-/// Trait for pixel types compatible with [`tex_image_2d`](EzGl::tex_image_2d)
-///
-/// ## Safety
-/// * You cannot implement this trait.
-pub unsafe trait TexImage2dPixelTy {
-  const INTERNAL_FORMAT: GLint;
-  const FORMAT: GLenum;
-  const TY: GLenum;
-}
-unsafe impl TexImage2dPixelTy for r8g8b8_Srgb {
-  const INTERNAL_FORMAT: GLint = GL_SRGB8 as _;
-  const FORMAT: GLenum = GL_RGB;
-  const TY: GLenum = GL_UNSIGNED_BYTE;
-}
-unsafe impl TexImage2dPixelTy for r8g8b8_Unorm {
-  const INTERNAL_FORMAT: GLint = GL_RGB as _;
-  const FORMAT: GLenum = GL_RGB;
-  const TY: GLenum = GL_UNSIGNED_BYTE;
-}
-unsafe impl TexImage2dPixelTy for r8g8b8a8_Srgb {
-  const INTERNAL_FORMAT: GLint = GL_SRGB8_ALPHA8 as _;
-  const FORMAT: GLenum = GL_RGBA;
-  const TY: GLenum = GL_UNSIGNED_BYTE;
-}
-unsafe impl TexImage2dPixelTy for r8g8b8a8_Unorm {
-  const INTERNAL_FORMAT: GLint = GL_RGBA32F as _;
-  const FORMAT: GLenum = GL_RGBA;
-  const TY: GLenum = GL_UNSIGNED_BYTE;
-}
-unsafe impl TexImage2dPixelTy for r32g32b32a32_Sfloat {
-  const INTERNAL_FORMAT: GLint = GL_RGBA8 as _;
-  const FORMAT: GLenum = GL_RGBA;
-  const TY: GLenum = GL_FLOAT;
-}
 
-impl EzGl {
-  /// Declares attribute info for attributes that will be float vecs within the
-  /// shader.
+  /// Draws using the element buffer, with `base_vertex` added to each element
+  /// value before it's used to read the vertex data.
   ///
-  /// The `BufferTy` generic should be the array type of the data *in the
-  /// buffer* for this attribute. The data within the shader will be an equal
-  /// length vector of floats. The GPU will transform the data on load as
-  /// necessary.
+  /// This lets many meshes share one vertex buffer and one element buffer
+  /// while each mesh's elements still start counting from 0.
   ///
-  /// * `index`: The attribute pointer index to change
-  /// * `stride`: The size of an entire vertex (all attributes combined).
-  /// * `offset`: The offset of this attribute within the vertex.
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of elements data to draw.
+  /// * `base_vertex` is added to each element value.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  ///
+  /// ## Safety
+  /// * The vertex attrib pointers must have been properly configured to match
+  ///   the vertex buffer data.
+  /// * The arguments to this function must not cause the vertex or element
+  ///   buffers to be read out of bounds, or you must have configured the
+  ///   context for robust buffer access.
   #[inline]
-  pub fn vertex_attrib_f32_pointer<BufferTy: VertexAttribPointerTy>(
-    &self, index: GLuint, stride: usize, offset: usize,
+  pub unsafe fn draw_elements_base_vertex<T: DrawElementsType>(
+    &self, mode: DrawMode, range: Range<usize>, base_vertex: i32,
   ) {
-    unsafe {
-      self.VertexAttribPointer(
-        index,
-        BufferTy::SIZE,
-        BufferTy::TY,
-        BufferTy::NORMALIZED,
-        stride.try_into().unwrap(),
-        offset as *const c_void,
-      )
-    }
+    assert!(range.start < range.end);
+    let base = range.start;
+    let count = range.end - range.start;
+    self.DrawElementsBaseVertex(
+      mode as GLenum,
+      count.try_into().unwrap(),
+      T::TY,
+      base as *const c_void,
+      base_vertex,
+    )
   }
-}
 
-/// ## Safety
-/// * You are not allowed to implement this trait.
+  /// Draws using the element buffer, promising that all element values in
+  /// the range are within `values`.
+  ///
+  /// This lets the driver know what part of the vertex data is used without
+  /// having to scan the elements.
+  ///
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of elements data to draw.
+  /// * `values` is the `min..=max` span of element values within `range`.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  /// * `assert!(values.start() <= values.end());`
+  ///
+  /// ## Safety
+  /// * The vertex attrib pointers must have been properly configured to match
+  ///   the vertex buffer data.
+  /// * The arguments to this function must not cause the vertex or element
+  ///   buffers to be read out of bounds, or you must have configured the
+  ///   context for robust buffer access.
+  /// * Element values outside of `values` give undefined results.
+  #[inline]
+  pub unsafe fn draw_range_elements<T: DrawElementsType>(
+    &self, mode: DrawMode, range: Range<usize>, values: RangeInclusive<u32>,
+  ) {
+    assert!(range.start < range.end);
+    assert!(values.start() <= values.end());
+    let base = range.start;
+    let count = range.end - range.start;
+    self.DrawRangeElements(
+      mode as GLenum,
+      *values.start(),
+      *values.end(),
+      count.try_into().unwrap(),
+      T::TY,
+      base as *const c_void,
+    )
+  }
+
+  /// As [`draw_range_elements`](Self::draw_range_elements), with `base_vertex`
+  /// added to each element value.
+  ///
+  /// The `values` span is of the element values *before* `base_vertex` is
+  /// added.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  /// * `assert!(values.start() <= values.end());`
+  ///
+  /// ## Safety
+  /// * Same as [`draw_range_elements`](Self::draw_range_elements).
+  #[inline]
+  pub unsafe fn draw_range_elements_base_vertex<T: DrawElementsType>(
+    &self, mode: DrawMode, range: Range<usize>, values: RangeInclusive<u32>,
+    base_vertex: i32,
+  ) {
+    assert!(range.start < range.end);
+    assert!(values.start() <= values.end());
+    let base = range.start;
+    let count = range.end - range.start;
+    self.DrawRangeElementsBaseVertex(
+      mode as GLenum,
+      *values.start(),
+      *values.end(),
+      count.try_into().unwrap(),
+      T::TY,
+      base as *const c_void,
+      base_vertex,
+    )
+  }
+
+  /// Draws several spans of the array buffer data with a single call.
+  ///
+  /// This is the same as calling [`draw_arrays`](Self::draw_arrays) once per
+  /// range.
+  ///
+  /// Not available on GLES.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);` for each range.
+  ///
+  /// ## Safety
+  /// * Same as [`draw_arrays`](Self::draw_arrays), for each range.
+  #[inline]
+  pub unsafe fn multi_draw_arrays(&self, mode: DrawMode, ranges: &[Range<usize>]) {
+    let mut firsts: Vec<GLint> = Vec::with_capacity(ranges.len());
+    let mut counts: Vec<GLsizei> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+      assert!(range.start < range.end);
+      firsts.push(range.start.try_into().unwrap());
+      counts.push((range.end - range.start).try_into().unwrap());
+    }
+    self.MultiDrawArrays(
+      mode as GLenum,
+      firsts.as_ptr(),
+      counts.as_ptr(),
+      ranges.len().try_into().unwrap(),
+    )
+  }
+
+  /// Draws several spans of the element buffer with a single call.
+  ///
+  /// This is the same as calling [`draw_elements`](Self::draw_elements) once
+  /// per range.
+  ///
+  /// Not available on GLES.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);` for each range.
+  ///
+  /// ## Safety
+  /// * Same as [`draw_elements`](Self::draw_elements), for each range.
+  #[inline]
+  pub unsafe fn multi_draw_elements<T: DrawElementsType>(
+    &self, mode: DrawMode, ranges: &[Range<usize>],
+  ) {
+    let (counts, offsets) = multi_draw_elements_args(ranges);
+    self.MultiDrawElements(
+      mode as GLenum,
+      counts.as_ptr(),
+      T::TY,
+      offsets.as_ptr(),
+      ranges.len().try_into().unwrap(),
+    )
+  }
+
+  /// Draws several spans of the element buffer with a single call, each with
+  /// its own base vertex.
+  ///
+  /// This is the same as calling
+  /// [`draw_elements_base_vertex`](Self::draw_elements_base_vertex) once per
+  /// range, using the base vertex with the same index.
+  ///
+  /// Not available on GLES.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);` for each range.
+  /// * `assert_eq!(ranges.len(), base_vertices.len());`
+  ///
+  /// ## Safety
+  /// * Same as [`draw_elements_base_vertex`](Self::draw_elements_base_vertex),
+  ///   for each range.
+  #[inline]
+  pub unsafe fn multi_draw_elements_base_vertex<T: DrawElementsType>(
+    &self, mode: DrawMode, ranges: &[Range<usize>], base_vertices: &[i32],
+  ) {
+    assert_eq!(ranges.len(), base_vertices.len());
+    let (counts, offsets) = multi_draw_elements_args(ranges);
+    self.MultiDrawElementsBaseVertex(
+      mode as GLenum,
+      counts.as_ptr(),
+      T::TY,
+      offsets.as_ptr(),
+      ranges.len().try_into().unwrap(),
+      base_vertices.as_ptr(),
+    )
+  }
+
+  /// Sets how often an attribute advances during an instanced draw.
+  ///
+  /// * 0 (the default): The attribute advances once per vertex.
+  /// * N: The attribute advances once every N instances.
+  #[inline]
+  pub fn set_vertex_attrib_divisor(&self, index: GLuint, divisor: u32) {
+    unsafe { self.VertexAttribDivisor(index, divisor) }
+  }
+
+  /// Draws `instance_count` instances using the array buffer data.
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of array data to draw.
+  /// * `instance_count` is the number of instances to draw.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  ///
+  /// ## Safety
+  /// * The attrib pointers must have been properly configured
+  /// * The arguments to this function must not cause the buffer data to be read
+  ///   out of bounds, including any per-instance attributes.
+  #[inline]
+  pub unsafe fn draw_arrays_instanced(
+    &self, mode: DrawMode, range: Range<usize>, instance_count: usize,
+  ) {
+    assert!(range.start < range.end);
+    let first = range.start;
+    let count = range.end - range.start;
+    self.DrawArraysInstanced(
+      mode as GLenum,
+      first.try_into().unwrap(),
+      count.try_into().unwrap(),
+      instance_count.try_into().unwrap(),
+    )
+  }
+
+  /// Draws instances using the array buffer data, with per-instance
+  /// attributes starting at a base instance.
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of array data to draw.
+  /// * `instances` is the `low..high` span of instances to draw. The low value
+  ///   offsets where per-instance attributes are read from, but `gl_InstanceID`
+  ///   still starts at 0.
+  ///
+  /// Requires GL 4.2 (not available on GLES).
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  /// * `assert!(instances.start <= instances.end);`
+  ///
+  /// ## Safety
+  /// * The attrib pointers must have been properly configured
+  /// * The arguments to this function must not cause the buffer data to be read
+  ///   out of bounds, including any per-instance attributes.
+  #[inline]
+  pub unsafe fn draw_arrays_instanced_base_instance(
+    &self, mode: DrawMode, range: Range<usize>, instances: Range<usize>,
+  ) {
+    assert!(range.start < range.end);
+    assert!(instances.start <= instances.end);
+    let first = range.start;
+    let count = range.end - range.start;
+    let instance_count = instances.end - instances.start;
+    self.DrawArraysInstancedBaseInstance(
+      mode as GLenum,
+      first.try_into().unwrap(),
+      count.try_into().unwrap(),
+      instance_count.try_into().unwrap(),
+      instances.start.try_into().unwrap(),
+    )
+  }
+
+  /// Draws `instance_count` instances using the element buffer.
+  ///
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of elements data to draw.
+  /// * `instance_count` is the number of instances to draw.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  ///
+  /// ## Safety
+  /// * The vertex attrib pointers must have been properly configured to match
+  ///   the vertex buffer data.
+  /// * The arguments to this function must not cause the vertex or element
+  ///   buffers to be read out of bounds (including any per-instance
+  ///   attributes), or you must have configured the context for robust buffer
+  ///   access.
+  #[inline]
+  pub unsafe fn draw_elements_instanced<T: DrawElementsType>(
+    &self, mode: DrawMode, range: Range<usize>, instance_count: usize,
+  ) {
+    assert!(range.start < range.end);
+    let base = range.start;
+    let count = range.end - range.start;
+    self.DrawElementsInstanced(
+      mode as GLenum,
+      count.try_into().unwrap(),
+      T::TY,
+      base as *const c_void,
+      instance_count.try_into().unwrap(),
+    )
+  }
+
+  /// Draws instances using the element buffer, with per-instance attributes
+  /// starting at a base instance.
+  ///
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of elements data to draw.
+  /// * `instances` is the `low..high` span of instances to draw. The low value
+  ///   offsets where per-instance attributes are read from, but `gl_InstanceID`
+  ///   still starts at 0.
+  ///
+  /// Requires GL 4.2 (not available on GLES).
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  /// * `assert!(instances.start <= instances.end);`
+  ///
+  /// ## Safety
+  /// * The vertex attrib pointers must have been properly configured to match
+  ///   the vertex buffer data.
+  /// * The arguments to this function must not cause the vertex or element
+  ///   buffers to be read out of bounds (including any per-instance
+  ///   attributes), or you must have configured the context for robust buffer
+  ///   access.
+  #[inline]
+  pub unsafe fn draw_elements_instanced_base_instance<T: DrawElementsType>(
+    &self, mode: DrawMode, range: Range<usize>, instances: Range<usize>,
+  ) {
+    assert!(range.start < range.end);
+    assert!(instances.start <= instances.end);
+    let base = range.start;
+    let count = range.end - range.start;
+    let instance_count = instances.end - instances.start;
+    self.DrawElementsInstancedBaseInstance(
+      mode as GLenum,
+      count.try_into().unwrap(),
+      T::TY,
+      base as *const c_void,
+      instance_count.try_into().unwrap(),
+      instances.start.try_into().unwrap(),
+    )
+  }
+}
+
+/// Converts element ranges into the counts and offsets that the "multi draw
+/// elements" calls use, the same way [`EzGl::draw_elements`] does.
+fn multi_draw_elements_args(
+  ranges: &[Range<usize>],
+) -> (Vec<GLsizei>, Vec<*const c_void>) {
+  ranges
+    .iter()
+    .map(|range| {
+      assert!(range.start < range.end);
+      let count: GLsizei = (range.end - range.start).try_into().unwrap();
+      (count, range.start as *const c_void)
+    })
+    .unzip()
+}
+
+/// Types that can be used with a "draw elements" call.
+///
+/// ## Safety
+/// * You cannot implement this trait.
+pub unsafe trait DrawElementsType {
+  const TY: GLenum;
+}
+unsafe impl DrawElementsType for u8 {
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl DrawElementsType for u16 {
+  const TY: GLenum = GL_UNSIGNED_SHORT;
+}
+unsafe impl DrawElementsType for u32 {
+  const TY: GLenum = GL_UNSIGNED_INT;
+}
+
+impl EzGl {
+  /// Draws using the array buffer data, with the draw parameters read from the
+  /// [`DrawIndirectBuffer`](BufferTarget::DrawIndirectBuffer).
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `offset` is the byte offset of a [`DrawArraysIndirectCommand`] within
+  ///   the indirect buffer. It must be a multiple of 4.
+  ///
+  /// ## Panics
+  /// * `assert!(offset.is_multiple_of(4));`
+  ///
+  /// ## Safety
+  /// * The attrib pointers must have been properly configured
+  /// * The command must be within the indirect buffer, and the draw it
+  ///   describes must not cause the buffer data to be read out of bounds.
+  #[inline]
+  pub unsafe fn draw_arrays_indirect(&self, mode: DrawMode, offset: usize) {
+    assert!(offset.is_multiple_of(4));
+    self.DrawArraysIndirect(mode as GLenum, offset as *const c_void)
+  }
+
+  /// Draws using the element buffer, with the draw parameters read from the
+  /// [`DrawIndirectBuffer`](BufferTarget::DrawIndirectBuffer).
+  ///
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `offset` is the byte offset of a [`DrawElementsIndirectCommand`] within
+  ///   the indirect buffer. It must be a multiple of 4.
+  ///
+  /// ## Panics
+  /// * `assert!(offset.is_multiple_of(4));`
+  ///
+  /// ## Safety
+  /// * The vertex attrib pointers must have been properly configured to match
+  ///   the vertex buffer data.
+  /// * The command must be within the indirect buffer, and the draw it
+  ///   describes must not cause the vertex or element buffers to be read out
+  ///   of bounds.
+  #[inline]
+  pub unsafe fn draw_elements_indirect<T: DrawElementsType>(
+    &self, mode: DrawMode, offset: usize,
+  ) {
+    assert!(offset.is_multiple_of(4));
+    self.DrawElementsIndirect(mode as GLenum, T::TY, offset as *const c_void)
+  }
+
+  /// Performs one [`draw_arrays_indirect`](Self::draw_arrays_indirect) per
+  /// command, with a single call.
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `commands` is the `low..high` index span of tightly packed
+  ///   [`DrawArraysIndirectCommand`] values in the indirect buffer.
+  ///
+  /// Requires GL 4.3 (not available on GLES).
+  ///
+  /// ## Safety
+  /// * Same as [`draw_arrays_indirect`](Self::draw_arrays_indirect), for each
+  ///   command.
+  #[inline]
+  pub unsafe fn multi_draw_arrays_indirect(
+    &self, mode: DrawMode, commands: Range<usize>,
+  ) {
+    assert!(commands.start <= commands.end);
+    let stride = size_of::<DrawArraysIndirectCommand>();
+    self.MultiDrawArraysIndirect(
+      mode as GLenum,
+      (commands.start * stride) as *const c_void,
+      (commands.end - commands.start).try_into().unwrap(),
+      stride.try_into().unwrap(),
+    )
+  }
+
+  /// Performs one [`draw_elements_indirect`](Self::draw_elements_indirect)
+  /// per command, with a single call.
+  ///
+  /// * `T` is the type of data in the elements buffer.
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `commands` is the `low..high` index span of tightly packed
+  ///   [`DrawElementsIndirectCommand`] values in the indirect buffer.
+  ///
+  /// Requires GL 4.3 (not available on GLES).
+  ///
+  /// ## Safety
+  /// * Same as [`draw_elements_indirect`](Self::draw_elements_indirect), for
+  ///   each command.
+  #[inline]
+  pub unsafe fn multi_draw_elements_indirect<T: DrawElementsType>(
+    &self, mode: DrawMode, commands: Range<usize>,
+  ) {
+    assert!(commands.start <= commands.end);
+    let stride = size_of::<DrawElementsIndirectCommand>();
+    self.MultiDrawElementsIndirect(
+      mode as GLenum,
+      T::TY,
+      (commands.start * stride) as *const c_void,
+      (commands.end - commands.start).try_into().unwrap(),
+      stride.try_into().unwrap(),
+    )
+  }
+
+  /// As [`multi_draw_arrays_indirect`](Self::multi_draw_arrays_indirect), but
+  /// the number of commands is also read from GPU memory.
+  ///
+  /// * `first_command` is the index of the first [`DrawArraysIndirectCommand`]
+  ///   in the indirect buffer.
+  /// * `count_offset` is the byte offset of a `u32` draw count in the buffer
+  ///   bound to [`ParameterBuffer`](BufferTarget::ParameterBuffer). It must be
+  ///   a multiple of 4.
+  /// * `max_draw_count` limits the number of commands, regardless of the count
+  ///   in the parameter buffer.
+  ///
+  /// Requires GL 4.6 or `ARB_indirect_parameters` (not available on GLES).
+  ///
+  /// ## Panics
+  /// * `assert!(count_offset.is_multiple_of(4));`
+  ///
+  /// ## Safety
+  /// * Same as [`draw_arrays_indirect`](Self::draw_arrays_indirect), for up to
+  ///   `max_draw_count` commands.
+  /// * The draw count must be within the parameter buffer.
+  #[inline]
+  pub unsafe fn multi_draw_arrays_indirect_count(
+    &self, mode: DrawMode, first_command: usize, count_offset: usize,
+    max_draw_count: usize,
+  ) {
+    assert!(count_offset.is_multiple_of(4));
+    let stride = size_of::<DrawArraysIndirectCommand>();
+    self.MultiDrawArraysIndirectCount(
+      mode as GLenum,
+      (first_command * stride) as *const c_void,
+      count_offset.try_into().unwrap(),
+      max_draw_count.try_into().unwrap(),
+      stride.try_into().unwrap(),
+    )
+  }
+
+  /// As [`multi_draw_elements_indirect`](Self::multi_draw_elements_indirect),
+  /// but the number of commands is also read from GPU memory.
+  ///
+  /// * `first_command` is the index of the first
+  ///   [`DrawElementsIndirectCommand`] in the indirect buffer.
+  /// * `count_offset` is the byte offset of a `u32` draw count in the buffer
+  ///   bound to [`ParameterBuffer`](BufferTarget::ParameterBuffer). It must be
+  ///   a multiple of 4.
+  /// * `max_draw_count` limits the number of commands, regardless of the count
+  ///   in the parameter buffer.
+  ///
+  /// Requires GL 4.6 or `ARB_indirect_parameters` (not available on GLES).
+  ///
+  /// ## Panics
+  /// * `assert!(count_offset.is_multiple_of(4));`
+  ///
+  /// ## Safety
+  /// * Same as [`draw_elements_indirect`](Self::draw_elements_indirect), for
+  ///   up to `max_draw_count` commands.
+  /// * The draw count must be within the parameter buffer.
+  #[inline]
+  pub unsafe fn multi_draw_elements_indirect_count<T: DrawElementsType>(
+    &self, mode: DrawMode, first_command: usize, count_offset: usize,
+    max_draw_count: usize,
+  ) {
+    assert!(count_offset.is_multiple_of(4));
+    let stride = size_of::<DrawElementsIndirectCommand>();
+    self.MultiDrawElementsIndirectCount(
+      mode as GLenum,
+      T::TY,
+      (first_command * stride) as *const c_void,
+      count_offset.try_into().unwrap(),
+      max_draw_count.try_into().unwrap(),
+      stride.try_into().unwrap(),
+    )
+  }
+}
+
+/// The parameters of one indirect "draw arrays" call, as stored in the
+/// [`DrawIndirectBuffer`](BufferTarget::DrawIndirectBuffer).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct DrawArraysIndirectCommand {
+  /// The number of vertices.
+  pub count: u32,
+  pub instance_count: u32,
+  /// The index of the first vertex.
+  pub first: u32,
+  /// Must be 0 before GL 4.2.
+  pub base_instance: u32,
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for DrawArraysIndirectCommand {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for DrawArraysIndirectCommand {}
+
+/// The parameters of one indirect "draw elements" call, as stored in the
+/// [`DrawIndirectBuffer`](BufferTarget::DrawIndirectBuffer).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct DrawElementsIndirectCommand {
+  /// The number of elements.
+  pub count: u32,
+  pub instance_count: u32,
+  /// The index (not byte offset) of the first element.
+  pub first_index: u32,
+  /// Added to each element value.
+  pub base_vertex: i32,
+  /// Must be 0 before GL 4.2.
+  pub base_instance: u32,
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for DrawElementsIndirectCommand {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for DrawElementsIndirectCommand {}
+
+/// The parameters of one indirect compute dispatch, as stored in the
+/// [`DispatchIndirectBuffer`](BufferTarget::DispatchIndirectBuffer).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct DispatchIndirectCommand {
+  pub num_groups_x: u32,
+  pub num_groups_y: u32,
+  pub num_groups_z: u32,
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for DispatchIndirectCommand {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for DispatchIndirectCommand {}
+
+/// Trait for pixel types compatible with [`tex_image_2d`](EzGl::tex_image_2d)
+///
+/// ## Safety
+/// * You cannot implement this trait.
+pub unsafe trait TexImage2dPixelTy {
+  const INTERNAL_FORMAT: GLint;
+  const FORMAT: GLenum;
+  const TY: GLenum;
+}
+unsafe impl TexImage2dPixelTy for r8g8b8_Srgb {
+  const INTERNAL_FORMAT: GLint = GL_SRGB8 as _;
+  const FORMAT: GLenum = GL_RGB;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl TexImage2dPixelTy for r8g8b8_Unorm {
+  const INTERNAL_FORMAT: GLint = GL_RGB as _;
+  const FORMAT: GLenum = GL_RGB;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl TexImage2dPixelTy for r8g8b8a8_Srgb {
+  const INTERNAL_FORMAT: GLint = GL_SRGB8_ALPHA8 as _;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl TexImage2dPixelTy for r8g8b8a8_Unorm {
+  const INTERNAL_FORMAT: GLint = GL_RGBA32F as _;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl TexImage2dPixelTy for r32g32b32a32_Sfloat {
+  const INTERNAL_FORMAT: GLint = GL_RGBA8 as _;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_FLOAT;
+}
+
+impl EzGl {
+  /// Declares attribute info for attributes that will be float vecs within the
+  /// shader.
+  ///
+  /// The `BufferTy` generic should be the array type of the data *in the
+  /// buffer* for this attribute. The data within the shader will be an equal
+  /// length vector of floats. The GPU will transform the data on load as
+  /// necessary.
+  ///
+  /// * `index`: The attribute pointer index to change
+  /// * `stride`: The size of an entire vertex (all attributes combined).
+  /// * `offset`: The offset of this attribute within the vertex.
+  #[inline]
+  pub fn vertex_attrib_f32_pointer<BufferTy: VertexAttribPointerTy>(
+    &self, index: GLuint, stride: usize, offset: usize,
+  ) {
+    unsafe {
+      self.VertexAttribPointer(
+        index,
+        BufferTy::SIZE,
+        BufferTy::TY,
+        BufferTy::NORMALIZED,
+        stride.try_into().unwrap(),
+        offset as *const c_void,
+      )
+    }
+  }
+  /// Declares attribute info for attributes that will be integer vecs
+  /// (`int`, `ivecN`, `uint`, `uvecN`) within the shader.
+  ///
+  /// The integer values in the buffer are passed to the shader unchanged,
+  /// with no conversion to float. Smaller buffer types are sign or zero
+  /// extended as appropriate.
+  ///
+  /// * `index`: The attribute pointer index to change
+  /// * `stride`: The size of an entire vertex (all attributes combined).
+  /// * `offset`: The offset of this attribute within the vertex.
+  #[inline]
+  pub fn vertex_attrib_int_pointer<BufferTy: VertexAttribIPointerTy>(
+    &self, index: GLuint, stride: usize, offset: usize,
+  ) {
+    unsafe {
+      self.VertexAttribIPointer(
+        index,
+        BufferTy::SIZE,
+        BufferTy::TY,
+        stride.try_into().unwrap(),
+        offset as *const c_void,
+      )
+    }
+  }
+  /// Declares attribute info for attributes that will be double vecs
+  /// (`double`, `dvecN`) within the shader.
+  ///
+  /// Requires desktop GL 4.1 (not available on GLES).
+  ///
+  /// * `index`: The attribute pointer index to change
+  /// * `stride`: The size of an entire vertex (all attributes combined).
+  /// * `offset`: The offset of this attribute within the vertex.
+  #[inline]
+  pub fn vertex_attrib_f64_pointer<BufferTy: VertexAttribLPointerTy>(
+    &self, index: GLuint, stride: usize, offset: usize,
+  ) {
+    unsafe {
+      self.VertexAttribLPointer(
+        index,
+        BufferTy::SIZE,
+        BufferTy::TY,
+        stride.try_into().unwrap(),
+        offset as *const c_void,
+      )
+    }
+  }
+}
+
+/// Types that can be the buffer data of a float attribute (see
+/// [`vertex_attrib_f32_pointer`](EzGl::vertex_attrib_f32_pointer)).
+///
+/// * Float types (`f32` and [`Half`]) are used as-is.
+/// * Integer types are converted directly to float (`255_u8` becomes `255.0`).
+/// * Integer types wrapped in [`Normalized`] are mapped to the `0.0 ..= 1.0`
+///   range (unsigned) or the `-1.0 ..= 1.0` range (signed).
+/// * The packed 2-10-10-10 types always have 4 components.
+///
+/// ## Safety
+/// * You are not allowed to implement this trait.
 pub unsafe trait VertexAttribPointerTy {
   const SIZE: GLint;
   const TY: GLenum;
-  const NORMALIZED: GLboolean;
+  const NORMALIZED: GLboolean;
+}
+macro_rules! impl_vertex_attrib_pointer_ty {
+  ($t:ty, $gl_ty:expr) => {
+    impl_vertex_attrib_pointer_ty!(@one $t, 1, $gl_ty, FALSE);
+    impl_vertex_attrib_pointer_ty!(@one [$t; 1], 1, $gl_ty, FALSE);
+    impl_vertex_attrib_pointer_ty!(@one [$t; 2], 2, $gl_ty, FALSE);
+    impl_vertex_attrib_pointer_ty!(@one [$t; 3], 3, $gl_ty, FALSE);
+    impl_vertex_attrib_pointer_ty!(@one [$t; 4], 4, $gl_ty, FALSE);
+  };
+  (normalizable $t:ty, $gl_ty:expr) => {
+    impl_vertex_attrib_pointer_ty!($t, $gl_ty);
+    impl_vertex_attrib_pointer_ty!(@one Normalized<$t>, 1, $gl_ty, TRUE);
+    impl_vertex_attrib_pointer_ty!(@one Normalized<[$t; 1]>, 1, $gl_ty, TRUE);
+    impl_vertex_attrib_pointer_ty!(@one Normalized<[$t; 2]>, 2, $gl_ty, TRUE);
+    impl_vertex_attrib_pointer_ty!(@one Normalized<[$t; 3]>, 3, $gl_ty, TRUE);
+    impl_vertex_attrib_pointer_ty!(@one Normalized<[$t; 4]>, 4, $gl_ty, TRUE);
+  };
+  (@one $t:ty, $size:expr, $gl_ty:expr, $normalized:ident) => {
+    unsafe impl VertexAttribPointerTy for $t {
+      const SIZE: GLint = $size;
+      const TY: GLenum = $gl_ty;
+      const NORMALIZED: GLboolean = GLboolean::$normalized;
+    }
+  };
+}
+impl_vertex_attrib_pointer_ty!(f32, GL_FLOAT);
+impl_vertex_attrib_pointer_ty!(Half, GL_HALF_FLOAT);
+impl_vertex_attrib_pointer_ty!(normalizable u8, GL_UNSIGNED_BYTE);
+impl_vertex_attrib_pointer_ty!(normalizable i8, GL_BYTE);
+impl_vertex_attrib_pointer_ty!(normalizable u16, GL_UNSIGNED_SHORT);
+impl_vertex_attrib_pointer_ty!(normalizable i16, GL_SHORT);
+impl_vertex_attrib_pointer_ty!(@one PackedU2_10_10_10, 4, GL_UNSIGNED_INT_2_10_10_10_REV, FALSE);
+impl_vertex_attrib_pointer_ty!(@one Normalized<PackedU2_10_10_10>, 4, GL_UNSIGNED_INT_2_10_10_10_REV, TRUE);
+impl_vertex_attrib_pointer_ty!(@one PackedI2_10_10_10, 4, GL_INT_2_10_10_10_REV, FALSE);
+impl_vertex_attrib_pointer_ty!(@one Normalized<PackedI2_10_10_10>, 4, GL_INT_2_10_10_10_REV, TRUE);
+
+/// Types that can be the buffer data of an integer attribute (see
+/// [`vertex_attrib_int_pointer`](EzGl::vertex_attrib_int_pointer)).
+///
+/// ## Safety
+/// * You are not allowed to implement this trait.
+pub unsafe trait VertexAttribIPointerTy {
+  const SIZE: GLint;
+  const TY: GLenum;
+}
+macro_rules! impl_vertex_attrib_i_pointer_ty {
+  ($t:ty, $gl_ty:expr) => {
+    impl_vertex_attrib_i_pointer_ty!(@one $t, 1, $gl_ty);
+    impl_vertex_attrib_i_pointer_ty!(@one [$t; 1], 1, $gl_ty);
+    impl_vertex_attrib_i_pointer_ty!(@one [$t; 2], 2, $gl_ty);
+    impl_vertex_attrib_i_pointer_ty!(@one [$t; 3], 3, $gl_ty);
+    impl_vertex_attrib_i_pointer_ty!(@one [$t; 4], 4, $gl_ty);
+  };
+  (@one $t:ty, $size:expr, $gl_ty:expr) => {
+    unsafe impl VertexAttribIPointerTy for $t {
+      const SIZE: GLint = $size;
+      const TY: GLenum = $gl_ty;
+    }
+  };
+}
+impl_vertex_attrib_i_pointer_ty!(u8, GL_UNSIGNED_BYTE);
+impl_vertex_attrib_i_pointer_ty!(i8, GL_BYTE);
+impl_vertex_attrib_i_pointer_ty!(u16, GL_UNSIGNED_SHORT);
+impl_vertex_attrib_i_pointer_ty!(i16, GL_SHORT);
+impl_vertex_attrib_i_pointer_ty!(u32, GL_UNSIGNED_INT);
+impl_vertex_attrib_i_pointer_ty!(i32, GL_INT);
+
+/// Types that can be the buffer data of a double attribute (see
+/// [`vertex_attrib_f64_pointer`](EzGl::vertex_attrib_f64_pointer)).
+///
+/// ## Safety
+/// * You are not allowed to implement this trait.
+pub unsafe trait VertexAttribLPointerTy {
+  const SIZE: GLint;
+  const TY: GLenum;
+}
+unsafe impl VertexAttribLPointerTy for f64 {
+  const SIZE: GLint = 1;
+  const TY: GLenum = GL_DOUBLE;
+}
+unsafe impl VertexAttribLPointerTy for [f64; 1] {
+  const SIZE: GLint = 1;
+  const TY: GLenum = GL_DOUBLE;
+}
+unsafe impl VertexAttribLPointerTy for [f64; 2] {
+  const SIZE: GLint = 2;
+  const TY: GLenum = GL_DOUBLE;
+}
+unsafe impl VertexAttribLPointerTy for [f64; 3] {
+  const SIZE: GLint = 3;
+  const TY: GLenum = GL_DOUBLE;
+}
+unsafe impl VertexAttribLPointerTy for [f64; 4] {
+  const SIZE: GLint = 4;
+  const TY: GLenum = GL_DOUBLE;
+}
+
+/// Marks integer vertex data as normalized.
+///
+/// When used as the buffer type of a float attribute the integer values are
+/// mapped to the `0.0 ..= 1.0` range (unsigned) or the `-1.0 ..= 1.0` range
+/// (signed). For example, `Normalized<[u8; 4]>` is the usual way to store a
+/// color in 4 bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Normalized<T>(pub T);
+#[cfg(feature = "bytemuck")]
+unsafe impl<T: bytemuck::Zeroable> bytemuck::Zeroable for Normalized<T> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl<T: bytemuck::Pod> bytemuck::Pod for Normalized<T> {}
+
+/// The bits of an IEEE 754 half precision float (`binary16`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Half(pub GLhalf);
+impl Half {
+  /// Converts an `f32` to the nearest `Half` value (ties to even).
+  ///
+  /// Values too large become infinity.
+  #[inline]
+  #[must_use]
+  pub const fn from_f32(f: f32) -> Self {
+    let x = f.to_bits();
+    let sign = ((x >> 16) & 0x8000) as u16;
+    let exp = ((x >> 23) & 0xFF) as i32;
+    let man = x & 0x7F_FFFF;
+    if exp == 0xFF {
+      let nan_bit = if man != 0 { 0x200 } else { 0 };
+      return Self(sign | 0x7C00 | nan_bit);
+    }
+    let e = exp - 127 + 15;
+    if e >= 0x1F {
+      Self(sign | 0x7C00)
+    } else if e <= 0 {
+      // The result is subnormal, or zero.
+      if e < -10 {
+        return Self(sign);
+      }
+      let man = man | 0x80_0000;
+      let shift = (14 - e) as u32;
+      let round_bit = 1 << (shift - 1);
+      let rem = man & ((round_bit << 1) - 1);
+      let mut h = (man >> shift) as u16;
+      if rem > round_bit || (rem == round_bit && (h & 1) != 0) {
+        h += 1;
+      }
+      Self(sign | h)
+    } else {
+      // Rounding can carry into the exponent, which is also correct.
+      let mut h = (((e as u32) << 10) | (man >> 13)) as u16;
+      let rem = man & 0x1FFF;
+      if rem > 0x1000 || (rem == 0x1000 && (h & 1) != 0) {
+        h += 1;
+      }
+      Self(sign | h)
+    }
+  }
+  /// Converts to `f32`, which is always exact.
+  #[inline]
+  #[must_use]
+  pub fn to_f32(self) -> f32 {
+    let h = self.0 as u32;
+    let sign = (h & 0x8000) << 16;
+    let exp = (h >> 10) & 0x1F;
+    let man = h & 0x3FF;
+    if exp == 0x1F {
+      f32::from_bits(sign | 0x7F80_0000 | (man << 13))
+    } else if exp == 0 {
+      // Subnormal (or zero), which is `man * 2^-24`.
+      let v = man as f32 * (1.0 / 16_777_216.0);
+      if sign != 0 {
+        -v
+      } else {
+        v
+      }
+    } else {
+      f32::from_bits(sign | ((exp + 112) << 23) | (man << 13))
+    }
+  }
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Half {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Half {}
+
+/// Four unsigned integers packed into a `u32` (`GL_UNSIGNED_INT_2_10_10_10_REV`).
+///
+/// From least to most significant bit: 10 bits x, 10 bits y, 10 bits z, 2 bits
+/// w.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+#[allow(non_camel_case_types)]
+pub struct PackedU2_10_10_10(pub u32);
+impl PackedU2_10_10_10 {
+  /// Packs the values, ignoring any bits that don't fit.
+  #[inline]
+  #[must_use]
+  pub const fn new(x: u32, y: u32, z: u32, w: u32) -> Self {
+    Self((x & 0x3FF) | ((y & 0x3FF) << 10) | ((z & 0x3FF) << 20) | ((w & 0b11) << 30))
+  }
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for PackedU2_10_10_10 {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for PackedU2_10_10_10 {}
+
+/// Four signed integers packed into a `u32` (`GL_INT_2_10_10_10_REV`).
+///
+/// Same bit layout as [`PackedU2_10_10_10`], but each value is two's
+/// complement. Wrapped in [`Normalized`] this is the usual compact format for
+/// normals and tangents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+#[allow(non_camel_case_types)]
+pub struct PackedI2_10_10_10(pub u32);
+impl PackedI2_10_10_10 {
+  /// Packs the values, ignoring any bits that don't fit.
+  #[inline]
+  #[must_use]
+  pub const fn new(x: i32, y: i32, z: i32, w: i32) -> Self {
+    let [x, y, z, w] = [x as u32, y as u32, z as u32, w as u32];
+    Self((x & 0x3FF) | ((y & 0x3FF) << 10) | ((z & 0x3FF) << 20) | ((w & 0b11) << 30))
+  }
+  /// Packs a normalized vector, with each component in `-1.0 ..= 1.0`.
+  #[inline]
+  #[must_use]
+  pub fn from_normalized(x: f32, y: f32, z: f32, w: f32) -> Normalized<Self> {
+    let f = |v: f32, max: f32| (v.clamp(-1.0, 1.0) * max).round() as i32;
+    Normalized(Self::new(f(x, 511.0), f(y, 511.0), f(z, 511.0), f(w, 1.0)))
+  }
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for PackedI2_10_10_10 {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for PackedI2_10_10_10 {}
+
+impl EzGl {
+  /// Enables and configures every attribute of the vertex type `V`.
+  ///
+  /// The attributes read from the buffer currently bound to
+  /// [`ArrayBuffer`](BufferTarget::ArrayBuffer), with a stride of the size of
+  /// `V`, and the configuration is stored in the currently bound vertex array.
+  #[inline]
+  pub fn apply_vertex_layout<V: Vertex>(&self) {
+    let stride = size_of::<V>();
+    for attr in V::ATTRIBUTES {
+      self.enable_vertex_attrib_array(attr.location);
+      let stride = stride.try_into().unwrap();
+      let offset = attr.offset as *const c_void;
+      unsafe {
+        match attr.kind {
+          VertexAttribKind::Float => self.VertexAttribPointer(
+            attr.location,
+            attr.size,
+            attr.ty,
+            attr.normalized,
+            stride,
+            offset,
+          ),
+          VertexAttribKind::Integer => {
+            self.VertexAttribIPointer(attr.location, attr.size, attr.ty, stride, offset)
+          }
+          VertexAttribKind::Double => {
+            self.VertexAttribLPointer(attr.location, attr.size, attr.ty, stride, offset)
+          }
+        }
+      }
+    }
+  }
+}
+
+/// The type of data that a vertex attribute is within the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexAttribKind {
+  /// Float vecs, set with `glVertexAttribPointer`.
+  Float,
+  /// Signed or unsigned integer vecs, set with `glVertexAttribIPointer`.
+  Integer,
+  /// Double vecs, set with `glVertexAttribLPointer`.
+  Double,
+}
+
+/// Describes one attribute of a [`Vertex`] type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+  /// The attribute location in the shader.
+  pub location: GLuint,
+  /// The type of the attribute within the shader.
+  pub kind: VertexAttribKind,
+  /// The number of components.
+  pub size: GLint,
+  /// The component type of the data in the buffer.
+  pub ty: GLenum,
+  /// If integer data is normalized when converted to float.
+  pub normalized: GLboolean,
+  /// The byte offset of the attribute within the vertex.
+  pub offset: usize,
+}
+impl VertexAttribute {
+  /// An attribute that's a float vec in the shader, with `BufferTy` as the
+  /// data in the buffer (see
+  /// [`vertex_attrib_f32_pointer`](EzGl::vertex_attrib_f32_pointer)).
+  #[inline]
+  #[must_use]
+  pub const fn float<BufferTy: VertexAttribPointerTy>(
+    location: GLuint, offset: usize,
+  ) -> Self {
+    Self {
+      location,
+      kind: VertexAttribKind::Float,
+      size: BufferTy::SIZE,
+      ty: BufferTy::TY,
+      normalized: BufferTy::NORMALIZED,
+      offset,
+    }
+  }
+  /// An attribute that's an integer vec in the shader, with `BufferTy` as the
+  /// data in the buffer (see
+  /// [`vertex_attrib_int_pointer`](EzGl::vertex_attrib_int_pointer)).
+  #[inline]
+  #[must_use]
+  pub const fn integer<BufferTy: VertexAttribIPointerTy>(
+    location: GLuint, offset: usize,
+  ) -> Self {
+    Self {
+      location,
+      kind: VertexAttribKind::Integer,
+      size: BufferTy::SIZE,
+      ty: BufferTy::TY,
+      normalized: GLboolean::FALSE,
+      offset,
+    }
+  }
+  /// An attribute that's a double vec in the shader, with `BufferTy` as the
+  /// data in the buffer (see
+  /// [`vertex_attrib_f64_pointer`](EzGl::vertex_attrib_f64_pointer)).
+  #[inline]
+  #[must_use]
+  pub const fn double<BufferTy: VertexAttribLPointerTy>(
+    location: GLuint, offset: usize,
+  ) -> Self {
+    Self {
+      location,
+      kind: VertexAttribKind::Double,
+      size: BufferTy::SIZE,
+      ty: BufferTy::TY,
+      normalized: GLboolean::FALSE,
+      offset,
+    }
+  }
+}
+
+/// A type that can be used as a vertex, with each field being an attribute.
+///
+/// Normally you'd use `#[derive(Vertex)]` (with the `derive` feature) rather
+/// than implementing this yourself.
+///
+/// ```ignore
+/// #[derive(Clone, Copy, Vertex)]
+/// #[repr(C)]
+/// struct MyVertex {
+///   pos: [f32; 3],
+///   #[vertex(location = 2)]
+///   uv: [f32; 2],
+///   #[vertex(integer)]
+///   bone_ids: [u8; 4],
+/// }
+/// ```
+///
+/// ## Safety
+/// * Every attribute must lie entirely within the type, and its size and
+///   component type must match the field at that offset.
+pub unsafe trait Vertex: Sized {
+  const ATTRIBUTES: &'static [VertexAttribute];
+}
+#[cfg(feature = "derive")]
+pub use ezgl_derive::{Std140, Std430, Vertex};
+
+impl EzGl {
+  /// Maps a byte range of the buffer bound to `target` into client memory.
+  ///
+  /// The returned guard gives access to the mapped bytes, and unmaps the
+  /// buffer when it drops. Use [`MappedBufferRange::unmap`] instead of dropping
+  /// the guard if you want to know if the data store was corrupted while
+  /// mapped.
+  ///
+  /// * The guard only allows reading the bytes if `access` includes
+  ///   [`MapAccess::READ`], and only allows writing them if `access` includes
+  ///   [`MapAccess::WRITE`].
+  /// * If `access` includes [`MapAccess::FLUSH_EXPLICIT`] you must flush any
+  ///   written ranges with [`flush_mapped_buffer_range`] before unmapping.
+  ///
+  /// ## Failure
+  /// * If GL can't map the range (eg: it's outside the buffer's storage, or
+  ///   the buffer is already mapped) you get an error.
+  ///
+  /// ## Panics
+  /// * `assert!(range.start < range.end);`
+  ///
+  /// ## Safety
+  /// * Until the guard is dropped (or unmapped), the buffer must stay bound to
+  ///   `target`, and must not be deleted, given new storage (eg: with
+  ///   [`buffer_data`](Self::buffer_data)), or unmapped with
+  ///   [`unmap_buffer`](Self::unmap_buffer). Any of these would free or unmap
+  ///   the memory that the guard points to.
+  ///
+  /// Khronos: [glMapBufferRange](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glMapBufferRange.xhtml)
+  ///
+  /// [`flush_mapped_buffer_range`]: Self::flush_mapped_buffer_range
+  #[inline]
+  pub unsafe fn map_buffer_range(
+    &self, target: BufferTarget, range: Range<usize>, access: MapAccess,
+  ) -> Result<MappedBufferRange<'_>, ()> {
+    assert!(range.start < range.end);
+    let len = range.end - range.start;
+    let ptr = self.MapBufferRange(
+      target as GLenum,
+      range.start.try_into().unwrap(),
+      len.try_into().unwrap(),
+      access.0,
+    );
+    if ptr.is_null() {
+      Err(())
+    } else {
+      Ok(MappedBufferRange { gl: self, target, ptr: ptr.cast::<u8>(), len, access })
+    }
+  }
+  /// Flushes writes to part of a mapping made with
+  /// [`MapAccess::FLUSH_EXPLICIT`].
+  ///
+  /// The `range` is relative to the start of the *mapped range*, not the start
+  /// of the buffer.
+  #[inline]
+  pub fn flush_mapped_buffer_range(&self, target: BufferTarget, range: Range<usize>) {
+    assert!(range.start <= range.end);
+    let len = range.end - range.start;
+    unsafe {
+      self.FlushMappedBufferRange(
+        target as GLenum,
+        range.start.try_into().unwrap(),
+        len.try_into().unwrap(),
+      )
+    }
+  }
+  /// Allocates immutable storage of `len` bytes for the buffer bound to
+  /// `target`.
+  ///
+  /// Unlike [`buffer_data`](Self::buffer_data) the storage can't be resized or
+  /// reallocated later, and the `flags` say exactly how it will be accessed.
+  /// This is what allows a buffer to stay mapped while GL uses it (see
+  /// [`create_persistent_mapped_buffer`](Self::create_persistent_mapped_buffer)).
+  ///
+  /// Requires GL 4.4 or `ARB_buffer_storage`.
+  ///
+  /// Khronos: [glBufferStorage](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glBufferStorage.xhtml)
+  #[inline]
+  pub fn buffer_storage(
+    &self, target: BufferTarget, len: usize, flags: BufferStorageFlags,
+  ) {
+    unsafe {
+      self.BufferStorage(target as GLenum, len.try_into().unwrap(), null(), flags.0)
+    }
+  }
+  /// As [`buffer_storage`](Self::buffer_storage), but the storage is
+  /// initialized with a copy of `data`.
+  #[inline]
+  pub fn buffer_storage_with_data(
+    &self, target: BufferTarget, data: &[u8], flags: BufferStorageFlags,
+  ) {
+    unsafe {
+      self.BufferStorage(
+        target as GLenum,
+        data.len().try_into().unwrap(),
+        data.as_ptr().cast::<c_void>(),
+        flags.0,
+      )
+    }
+  }
+  /// Unmaps the buffer bound to `target`.
+  ///
+  /// Normally [`MappedBufferRange`] calls this for you.
+  ///
+  /// ## Failure
+  /// * If the data store contents became corrupt while mapped (eg: a screen
+  ///   mode change), you get an error and should re-upload the data.
+  ///
+  /// ## Safety
+  /// * There must not be a live [`MappedBufferRange`] or
+  ///   [`PersistentMappedBuffer`] for the buffer, since its memory would be
+  ///   unmapped out from under it.
+  #[inline]
+  pub unsafe fn unmap_buffer(&self, target: BufferTarget) -> Result<(), ()> {
+    if bool::from(self.UnmapBuffer(target as GLenum)) {
+      Ok(())
+    } else {
+      Err(())
+    }
+  }
+}
+
+impl EzGl {
+  /// Reads data from the buffer bound to `target`, starting at `offset` bytes,
+  /// to fill all of `out`.
+  ///
+  /// GLES doesn't have `glGetBufferSubData`, so when that isn't loaded this maps
+  /// the range for reading and copies out of the mapping instead.
+  ///
+  /// ## Failure
+  /// * If the fallback mapping failed.
+  ///
+  /// Khronos: [glGetBufferSubData](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glGetBufferSubData.xhtml)
+  #[inline]
+  pub fn get_buffer_sub_data(
+    &self, target: BufferTarget, offset: usize, out: &mut [u8],
+  ) -> Result<(), ()> {
+    if out.is_empty() {
+      Ok(())
+    } else if self.has_loaded().GetBufferSubData() {
+      Ok(unsafe {
+        self.GetBufferSubData(
+          target as GLenum,
+          offset.try_into().unwrap(),
+          out.len().try_into().unwrap(),
+          out.as_mut_ptr().cast::<c_void>(),
+        )
+      })
+    } else {
+      // Safety: nothing else touches the buffer before the mapping is gone.
+      let mapping = unsafe {
+        self.map_buffer_range(target, offset..(offset + out.len()), MapAccess::READ)?
+      };
+      out.copy_from_slice(mapping.as_slice()?);
+      mapping.unmap()
+    }
+  }
+  /// As [`get_buffer_sub_data`](Self::get_buffer_sub_data), but with any
+  /// [`Pod`](bytemuck::Pod) slice.
+  ///
+  /// The `offset` is still in bytes, not elements.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn get_buffer_sub_data_pod<T: bytemuck::Pod>(
+    &self, target: BufferTarget, offset: usize, out: &mut [T],
+  ) -> Result<(), ()> {
+    self.get_buffer_sub_data(target, offset, bytemuck::cast_slice_mut(out))
+  }
+  /// Copies `len` bytes from the buffer bound to `read_target` into the buffer
+  /// bound to `write_target`.
+  ///
+  /// If both targets are the same buffer then the ranges must not overlap.
+  ///
+  /// Khronos: [glCopyBufferSubData](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glCopyBufferSubData.xhtml)
+  #[inline]
+  pub fn copy_buffer_sub_data(
+    &self, read_target: BufferTarget, write_target: BufferTarget, read_offset: usize,
+    write_offset: usize, len: usize,
+  ) {
+    unsafe {
+      self.CopyBufferSubData(
+        read_target as GLenum,
+        write_target as GLenum,
+        read_offset.try_into().unwrap(),
+        write_offset.try_into().unwrap(),
+        len.try_into().unwrap(),
+      )
+    }
+  }
+  /// Copies `len` bytes from one buffer to another.
+  ///
+  /// This binds `src` to [`CopyReadBuffer`](BufferTarget::CopyReadBuffer) and
+  /// `dst` to [`CopyWriteBuffer`](BufferTarget::CopyWriteBuffer), so that no
+  /// other bindings are disturbed.
+  #[inline]
+  pub fn copy_buffer_range(
+    &self, src: &BufferObject, src_offset: usize, dst: &BufferObject, dst_offset: usize,
+    len: usize,
+  ) {
+    self.bind_buffer(BufferTarget::CopyReadBuffer, src);
+    self.bind_buffer(BufferTarget::CopyWriteBuffer, dst);
+    self.copy_buffer_sub_data(
+      BufferTarget::CopyReadBuffer,
+      BufferTarget::CopyWriteBuffer,
+      src_offset,
+      dst_offset,
+      len,
+    )
+  }
+  /// Fills the entire buffer bound to `target` with copies of `value`.
+  ///
+  /// Requires GL 4.3 (not available on GLES).
+  ///
+  /// Khronos: [glClearBufferData](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glClearBufferData.xhtml)
+  #[inline]
+  pub fn clear_buffer_data<P: ClearBufferDataTy>(&self, target: BufferTarget, value: &P) {
+    unsafe {
+      self.ClearBufferData(
+        target as GLenum,
+        P::INTERNAL_FORMAT,
+        P::FORMAT,
+        P::TY,
+        (value as *const P).cast::<c_void>(),
+      )
+    }
+  }
+  /// Fills a byte range of the buffer bound to `target` with copies of
+  /// `value`.
+  ///
+  /// Requires GL 4.3 (not available on GLES).
+  ///
+  /// ## Panics
+  /// * If the start or end of the range isn't a multiple of the size of `P`.
+  #[inline]
+  pub fn clear_buffer_sub_data<P: ClearBufferDataTy>(
+    &self, target: BufferTarget, range: Range<usize>, value: &P,
+  ) {
+    assert!(range.start <= range.end);
+    assert!(range.start.is_multiple_of(size_of::<P>()));
+    assert!(range.end.is_multiple_of(size_of::<P>()));
+    let len = range.end - range.start;
+    unsafe {
+      self.ClearBufferSubData(
+        target as GLenum,
+        P::INTERNAL_FORMAT,
+        range.start.try_into().unwrap(),
+        len.try_into().unwrap(),
+        P::FORMAT,
+        P::TY,
+        (value as *const P).cast::<c_void>(),
+      )
+    }
+  }
+}
+
+/// Types that can be used with [`clear_buffer_data`](EzGl::clear_buffer_data).
+///
+/// ## Safety
+/// * You cannot implement this trait.
+pub unsafe trait ClearBufferDataTy {
+  const INTERNAL_FORMAT: GLenum;
+  const FORMAT: GLenum;
+  const TY: GLenum;
 }
-unsafe impl VertexAttribPointerTy for [f32; 2] {
-  const SIZE: GLint = 2;
-  const NORMALIZED: GLboolean = GLboolean::FALSE;
+unsafe impl ClearBufferDataTy for u8 {
+  const INTERNAL_FORMAT: GLenum = GL_R8UI;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl ClearBufferDataTy for u16 {
+  const INTERNAL_FORMAT: GLenum = GL_R16UI;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_UNSIGNED_SHORT;
+}
+unsafe impl ClearBufferDataTy for u32 {
+  const INTERNAL_FORMAT: GLenum = GL_R32UI;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_UNSIGNED_INT;
+}
+unsafe impl ClearBufferDataTy for i8 {
+  const INTERNAL_FORMAT: GLenum = GL_R8I;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_BYTE;
+}
+unsafe impl ClearBufferDataTy for i16 {
+  const INTERNAL_FORMAT: GLenum = GL_R16I;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_SHORT;
+}
+unsafe impl ClearBufferDataTy for i32 {
+  const INTERNAL_FORMAT: GLenum = GL_R32I;
+  const FORMAT: GLenum = GL_RED_INTEGER;
+  const TY: GLenum = GL_INT;
+}
+unsafe impl ClearBufferDataTy for f32 {
+  const INTERNAL_FORMAT: GLenum = GL_R32F;
+  const FORMAT: GLenum = GL_RED;
   const TY: GLenum = GL_FLOAT;
 }
-unsafe impl VertexAttribPointerTy for [f32; 3] {
-  const SIZE: GLint = 3;
-  const NORMALIZED: GLboolean = GLboolean::FALSE;
+unsafe impl ClearBufferDataTy for [f32; 2] {
+  const INTERNAL_FORMAT: GLenum = GL_RG32F;
+  const FORMAT: GLenum = GL_RG;
+  const TY: GLenum = GL_FLOAT;
+}
+unsafe impl ClearBufferDataTy for [f32; 3] {
+  const INTERNAL_FORMAT: GLenum = GL_RGB32F;
+  const FORMAT: GLenum = GL_RGB;
   const TY: GLenum = GL_FLOAT;
 }
+unsafe impl ClearBufferDataTy for [f32; 4] {
+  const INTERNAL_FORMAT: GLenum = GL_RGBA32F;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_FLOAT;
+}
+unsafe impl ClearBufferDataTy for [u32; 4] {
+  const INTERNAL_FORMAT: GLenum = GL_RGBA32UI;
+  const FORMAT: GLenum = GL_RGBA_INTEGER;
+  const TY: GLenum = GL_UNSIGNED_INT;
+}
+unsafe impl ClearBufferDataTy for r8g8b8a8_Unorm {
+  const INTERNAL_FORMAT: GLenum = GL_RGBA8;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_UNSIGNED_BYTE;
+}
+unsafe impl ClearBufferDataTy for r32g32b32a32_Sfloat {
+  const INTERNAL_FORMAT: GLenum = GL_RGBA32F;
+  const FORMAT: GLenum = GL_RGBA;
+  const TY: GLenum = GL_FLOAT;
+}
+
+/// Access flags for [`map_buffer_range`](EzGl::map_buffer_range).
+///
+/// Combine flags with `|`. You must include at least one of `READ` or `WRITE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct MapAccess(GLbitfield);
+impl MapAccess {
+  pub const READ: Self = Self(GL_MAP_READ_BIT);
+  pub const WRITE: Self = Self(GL_MAP_WRITE_BIT);
+  /// The previous contents of the mapped range may be discarded.
+  pub const INVALIDATE_RANGE: Self = Self(GL_MAP_INVALIDATE_RANGE_BIT);
+  /// The previous contents of the entire buffer may be discarded.
+  pub const INVALIDATE_BUFFER: Self = Self(GL_MAP_INVALIDATE_BUFFER_BIT);
+  /// Writes are only made visible with
+  /// [`flush_mapped_buffer_range`](EzGl::flush_mapped_buffer_range).
+  pub const FLUSH_EXPLICIT: Self = Self(GL_MAP_FLUSH_EXPLICIT_BIT);
+  /// GL won't wait for pending operations on the buffer before mapping.
+  pub const UNSYNCHRONIZED: Self = Self(GL_MAP_UNSYNCHRONIZED_BIT);
+  /// The buffer can stay mapped while GL uses it. Requires immutable storage
+  /// made with [`BufferStorageFlags::MAP_PERSISTENT`].
+  pub const PERSISTENT: Self = Self(GL_MAP_PERSISTENT_BIT);
+  /// Persistent mappings are kept coherent without explicit flushes or
+  /// barriers. Requires [`BufferStorageFlags::MAP_COHERENT`].
+  pub const COHERENT: Self = Self(GL_MAP_COHERENT_BIT);
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.0 & other.0) == other.0
+  }
+}
+impl core::ops::BitOr for MapAccess {
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    Self(self.0 | rhs.0)
+  }
+}
+impl core::ops::BitOrAssign for MapAccess {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    self.0 |= rhs.0
+  }
+}
+
+/// A mapped range of a buffer's data store.
+///
+/// Made with [`map_buffer_range`](EzGl::map_buffer_range), and unmaps the
+/// buffer on drop.
+pub struct MappedBufferRange<'gl> {
+  gl: &'gl EzGl,
+  target: BufferTarget,
+  ptr: *mut u8,
+  len: usize,
+  access: MapAccess,
+}
+impl MappedBufferRange<'_> {
+  /// The access flags that the range was mapped with.
+  #[inline]
+  #[must_use]
+  pub fn access(&self) -> MapAccess {
+    self.access
+  }
+  /// Views the mapped bytes.
+  ///
+  /// ## Failure
+  /// * The range wasn't mapped with [`MapAccess::READ`].
+  #[inline]
+  pub fn as_slice(&self) -> Result<&[u8], ()> {
+    if self.access.contains(MapAccess::READ) {
+      Ok(unsafe { slice_from_raw_parts(self.ptr, self.len) })
+    } else {
+      Err(())
+    }
+  }
+  /// Mutably views the mapped bytes.
+  ///
+  /// A mutable slice can be read from as well, so this needs both access bits.
+  /// Use [`write`](Self::write) with a write-only mapping.
+  ///
+  /// ## Failure
+  /// * The range wasn't mapped with both [`MapAccess::READ`] and
+  ///   [`MapAccess::WRITE`].
+  #[inline]
+  pub fn as_mut_slice(&mut self) -> Result<&mut [u8], ()> {
+    if self.access.contains(MapAccess::READ | MapAccess::WRITE) {
+      Ok(unsafe { core::slice::from_raw_parts_mut(self.ptr, self.len) })
+    } else {
+      Err(())
+    }
+  }
+  /// Copies `data` into the mapping, starting `offset` bytes in.
+  ///
+  /// ## Failure
+  /// * The range wasn't mapped with [`MapAccess::WRITE`].
+  ///
+  /// ## Panics
+  /// * If the data would go past the end of the mapping.
+  #[inline]
+  pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), ()> {
+    assert!(offset.checked_add(data.len()).is_some_and(|end| end <= self.len));
+    if self.access.contains(MapAccess::WRITE) {
+      unsafe { self.ptr.add(offset).copy_from_nonoverlapping(data.as_ptr(), data.len()) };
+      Ok(())
+    } else {
+      Err(())
+    }
+  }
+  /// Flushes writes to part of the mapping (see
+  /// [`flush_mapped_buffer_range`](EzGl::flush_mapped_buffer_range)).
+  ///
+  /// ## Panics
+  /// * If `range` goes past the end of the mapping.
+  #[inline]
+  pub fn flush(&self, range: Range<usize>) {
+    assert!(range.end <= self.len);
+    self.gl.flush_mapped_buffer_range(self.target, range)
+  }
+  /// Unmaps the buffer, reporting if the data was corrupted while mapped.
+  #[inline]
+  pub fn unmap(self) -> Result<(), ()> {
+    let this = core::mem::ManuallyDrop::new(self);
+    unsafe { this.gl.unmap_buffer(this.target) }
+  }
+  /// As [`as_slice`](Self::as_slice), but viewed as [`Pod`](bytemuck::Pod)
+  /// values.
+  ///
+  /// ## Panics
+  /// * If the mapping isn't aligned for `T` or its size isn't a multiple of
+  ///   `T`'s size.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn as_pod_slice<T: bytemuck::Pod>(&self) -> Result<&[T], ()> {
+    self.as_slice().map(bytemuck::cast_slice)
+  }
+  /// As [`as_mut_slice`](Self::as_mut_slice), but viewed as
+  /// [`Pod`](bytemuck::Pod) values.
+  ///
+  /// ## Panics
+  /// * If the mapping isn't aligned for `T` or its size isn't a multiple of
+  ///   `T`'s size.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn as_pod_slice_mut<T: bytemuck::Pod>(&mut self) -> Result<&mut [T], ()> {
+    self.as_mut_slice().map(bytemuck::cast_slice_mut)
+  }
+  /// As [`write`](Self::write), but with any [`Pod`](bytemuck::Pod) slice.
+  ///
+  /// The `offset` is still in bytes, not elements.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn write_pod<T: bytemuck::Pod>(
+    &mut self, offset: usize, data: &[T],
+  ) -> Result<(), ()> {
+    self.write(offset, bytemuck::cast_slice(data))
+  }
+}
+impl Drop for MappedBufferRange<'_> {
+  #[inline]
+  fn drop(&mut self) {
+    // There's no way to report the error from here, so callers that care use
+    // `unmap` instead.
+    let _ = unsafe { self.gl.unmap_buffer(self.target) };
+  }
+}
+
+/// Flags for [`buffer_storage`](EzGl::buffer_storage).
+///
+/// Combine flags with `|`. The default (no flags) is storage that can only be
+/// changed by GL itself (eg: `glCopyBufferSubData` or shader writes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct BufferStorageFlags(GLbitfield);
+impl BufferStorageFlags {
+  /// The contents can be updated with
+  /// [`buffer_sub_data`](EzGl::buffer_sub_data).
+  pub const DYNAMIC_STORAGE: Self = Self(GL_DYNAMIC_STORAGE_BIT);
+  pub const MAP_READ: Self = Self(GL_MAP_READ_BIT);
+  pub const MAP_WRITE: Self = Self(GL_MAP_WRITE_BIT);
+  /// The buffer can be used by GL while it's mapped.
+  pub const MAP_PERSISTENT: Self = Self(GL_MAP_PERSISTENT_BIT);
+  /// Persistent mappings of the buffer can be coherent.
+  pub const MAP_COHERENT: Self = Self(GL_MAP_COHERENT_BIT);
+  /// Hint that the storage should live in client memory.
+  pub const CLIENT_STORAGE: Self = Self(GL_CLIENT_STORAGE_BIT);
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.0 & other.0) == other.0
+  }
+}
+impl core::ops::BitOr for BufferStorageFlags {
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    Self(self.0 | rhs.0)
+  }
+}
+impl core::ops::BitOrAssign for BufferStorageFlags {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    self.0 |= rhs.0
+  }
+}
+
+impl EzGl {
+  /// Makes a new buffer with immutable storage for `region_count` regions of
+  /// `region_size` bytes each, and maps the whole thing persistently.
+  ///
+  /// The typical use is one region per frame in flight: each frame you write
+  /// into the next region while GL is still reading from the others.
+  ///
+  /// * `flags` must include [`MAP_PERSISTENT`] and at least one of
+  ///   [`MAP_READ`] or [`MAP_WRITE`]. If it doesn't include [`MAP_COHERENT`]
+  ///   the mapping uses explicit flushing, and you must call
+  ///   [`PersistentMappedBuffer::flush_region`] after writing.
+  /// * The new buffer is left bound to `target`.
+  /// * If you'll bind regions with `bind_buffer_range` then `region_size` must
+  ///   be a multiple of the target's offset alignment (eg:
+  ///   `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`).
+  ///
+  /// Requires GL 4.4 or `ARB_buffer_storage`.
+  ///
+  /// ## Panics
+  /// * If the flags aren't valid for a persistent mapping.
+  ///
+  /// [`MAP_PERSISTENT`]: BufferStorageFlags::MAP_PERSISTENT
+  /// [`MAP_READ`]: BufferStorageFlags::MAP_READ
+  /// [`MAP_WRITE`]: BufferStorageFlags::MAP_WRITE
+  /// [`MAP_COHERENT`]: BufferStorageFlags::MAP_COHERENT
+  #[inline]
+  pub fn create_persistent_mapped_buffer(
+    &self, target: BufferTarget, region_size: usize, region_count: usize,
+    flags: BufferStorageFlags,
+  ) -> Result<PersistentMappedBuffer, ()> {
+    assert!(flags.contains(BufferStorageFlags::MAP_PERSISTENT));
+    assert!(
+      flags.contains(BufferStorageFlags::MAP_READ)
+        || flags.contains(BufferStorageFlags::MAP_WRITE)
+    );
+    assert!(region_size > 0 && region_count > 0);
+    let len = region_size.checked_mul(region_count).unwrap();
+    let mut access = MapAccess::PERSISTENT;
+    if flags.contains(BufferStorageFlags::MAP_READ) {
+      access |= MapAccess::READ;
+    }
+    if flags.contains(BufferStorageFlags::MAP_WRITE) {
+      access |= MapAccess::WRITE;
+    }
+    if flags.contains(BufferStorageFlags::MAP_COHERENT) {
+      access |= MapAccess::COHERENT;
+    } else if access.contains(MapAccess::WRITE) {
+      access |= MapAccess::FLUSH_EXPLICIT;
+    }
+    let buffer = self.gen_buffer()?;
+    self.bind_buffer(target, &buffer);
+    self.buffer_storage(target, len, flags);
+    let ptr = unsafe {
+      self.MapBufferRange(target as GLenum, 0, len.try_into().unwrap(), access.0)
+    };
+    if ptr.is_null() {
+      self.delete_buffer(buffer);
+      Err(())
+    } else {
+      Ok(PersistentMappedBuffer {
+        buffer,
+        target,
+        ptr: ptr.cast::<u8>(),
+        region_size,
+        region_count,
+        access,
+      })
+    }
+  }
+  /// Unmaps and deletes a persistently mapped buffer.
+  ///
+  /// This binds the buffer to the target it was created with.
+  #[inline]
+  pub fn delete_persistent_mapped_buffer(&self, buffer: PersistentMappedBuffer) {
+    self.bind_buffer(buffer.target, &buffer.buffer);
+    let _ = unsafe { self.unmap_buffer(buffer.target) };
+    self.delete_buffer(buffer.buffer);
+  }
+}
+
+/// A buffer that stays mapped for its entire life, split into equal regions.
+///
+/// Made with
+/// [`create_persistent_mapped_buffer`](EzGl::create_persistent_mapped_buffer)
+/// and freed with
+/// [`delete_persistent_mapped_buffer`](EzGl::delete_persistent_mapped_buffer).
+///
+/// Writing to a region doesn't wait on GL in any way. You must not write to a
+/// region that pending GL commands might still read from (use a fence sync
+/// per region, or enough regions that it can't happen).
+///
+/// The mapping can only be read from if the buffer was made with
+/// [`MAP_READ`](BufferStorageFlags::MAP_READ), and only written to if it was
+/// made with [`MAP_WRITE`](BufferStorageFlags::MAP_WRITE).
+#[derive(Debug)]
+pub struct PersistentMappedBuffer {
+  buffer: BufferObject,
+  target: BufferTarget,
+  ptr: *mut u8,
+  region_size: usize,
+  region_count: usize,
+  access: MapAccess,
+}
+impl PersistentMappedBuffer {
+  /// The underlying buffer object, for binding.
+  #[inline]
+  #[must_use]
+  pub fn buffer(&self) -> &BufferObject {
+    &self.buffer
+  }
+  #[inline]
+  #[must_use]
+  pub fn region_size(&self) -> usize {
+    self.region_size
+  }
+  #[inline]
+  #[must_use]
+  pub fn region_count(&self) -> usize {
+    self.region_count
+  }
+  /// The byte offset of a region within the buffer.
+  ///
+  /// ## Panics
+  /// * If `index` is out of bounds.
+  #[inline]
+  #[must_use]
+  pub fn region_offset(&self, index: usize) -> usize {
+    assert!(index < self.region_count);
+    index * self.region_size
+  }
+  /// The byte range of a region within the buffer.
+  #[inline]
+  #[must_use]
+  pub fn region_range(&self, index: usize) -> Range<usize> {
+    let start = self.region_offset(index);
+    start..(start + self.region_size)
+  }
+  /// The access flags that the buffer was mapped with.
+  #[inline]
+  #[must_use]
+  pub fn access(&self) -> MapAccess {
+    self.access
+  }
+  /// Views the mapped memory of a region.
+  ///
+  /// ## Failure
+  /// * The buffer wasn't made with
+  ///   [`MAP_READ`](BufferStorageFlags::MAP_READ).
+  ///
+  /// ## Panics
+  /// * If `index` is out of bounds.
+  #[inline]
+  pub fn region(&self, index: usize) -> Result<&[u8], ()> {
+    let offset = self.region_offset(index);
+    if self.access.contains(MapAccess::READ) {
+      Ok(unsafe { slice_from_raw_parts(self.ptr.add(offset), self.region_size) })
+    } else {
+      Err(())
+    }
+  }
+  /// Mutably views the mapped memory of a region.
+  ///
+  /// A mutable slice can be read from as well, so this needs both access bits.
+  /// Use [`write_region`](Self::write_region) with a write-only buffer.
+  ///
+  /// ## Failure
+  /// * The buffer wasn't made with both
+  ///   [`MAP_READ`](BufferStorageFlags::MAP_READ) and
+  ///   [`MAP_WRITE`](BufferStorageFlags::MAP_WRITE).
+  ///
+  /// ## Panics
+  /// * If `index` is out of bounds.
+  #[inline]
+  pub fn region_mut(&mut self, index: usize) -> Result<&mut [u8], ()> {
+    let offset = self.region_offset(index);
+    if self.access.contains(MapAccess::READ | MapAccess::WRITE) {
+      Ok(unsafe {
+        core::slice::from_raw_parts_mut(self.ptr.add(offset), self.region_size)
+      })
+    } else {
+      Err(())
+    }
+  }
+  /// Copies `data` into a region, starting `offset` bytes into the region.
+  ///
+  /// ## Failure
+  /// * The buffer wasn't made with
+  ///   [`MAP_WRITE`](BufferStorageFlags::MAP_WRITE).
+  ///
+  /// ## Panics
+  /// * If `index` is out of bounds, or the data would go past the end of the
+  ///   region.
+  #[inline]
+  pub fn write_region(
+    &mut self, index: usize, offset: usize, data: &[u8],
+  ) -> Result<(), ()> {
+    let region_offset = self.region_offset(index);
+    assert!(offset.checked_add(data.len()).is_some_and(|end| end <= self.region_size));
+    if self.access.contains(MapAccess::WRITE) {
+      unsafe {
+        self
+          .ptr
+          .add(region_offset + offset)
+          .copy_from_nonoverlapping(data.as_ptr(), data.len())
+      };
+      Ok(())
+    } else {
+      Err(())
+    }
+  }
+  /// Makes writes to a region visible to GL.
+  ///
+  /// Only needed (and only does anything) when the buffer isn't coherent. The
+  /// buffer must be bound to the target it was created with.
+  #[inline]
+  pub fn flush_region(&self, gl: &EzGl, index: usize) {
+    if self.access.contains(MapAccess::FLUSH_EXPLICIT) {
+      gl.flush_mapped_buffer_range(self.target, self.region_range(index));
+    }
+  }
+}
+
+impl EzGl {
+  /// Inserts a fence into the command stream, which becomes signaled once all
+  /// commands before it have completed.
+  ///
+  /// Khronos: [glFenceSync](https://registry.khronos.org/OpenGL-Refpages/gl4/html/glFenceSync.xhtml)
+  #[inline]
+  pub fn fence_sync(&self) -> Result<SyncObject, ()> {
+    let sync = unsafe { self.FenceSync(GL_SYNC_GPU_COMMANDS_COMPLETE, 0) };
+    NonNull::new(sync).ok_or(()).map(SyncObject)
+  }
+  /// Blocks the CPU until the sync is signaled or `timeout_ns` passes.
+  ///
+  /// * `flush`: Flush the command stream first. If you don't do this and the
+  ///   fence hasn't been sent to the GPU yet, the wait can't ever succeed.
+  ///
+  /// ## Failure
+  /// * If GL reports `GL_WAIT_FAILED`.
+  #[inline]
+  pub fn client_wait_sync(
+    &self, sync: &SyncObject, flush: bool, timeout_ns: u64,
+  ) -> Result<SyncWaitStatus, ()> {
+    let flags = if flush { GL_SYNC_FLUSH_COMMANDS_BIT } else { 0 };
+    match unsafe { self.ClientWaitSync(sync.0.as_ptr(), flags, timeout_ns) } {
+      GL_ALREADY_SIGNALED => Ok(SyncWaitStatus::AlreadySignaled),
+      GL_CONDITION_SATISFIED => Ok(SyncWaitStatus::ConditionSatisfied),
+      GL_TIMEOUT_EXPIRED => Ok(SyncWaitStatus::TimeoutExpired),
+      _ => Err(()),
+    }
+  }
+  /// Makes the *GPU* wait for the sync before running any further commands.
+  ///
+  /// The CPU doesn't block.
+  #[inline]
+  pub fn wait_sync(&self, sync: &SyncObject) {
+    // `GL_TIMEOUT_IGNORED` is the only allowed timeout.
+    unsafe { self.WaitSync(sync.0.as_ptr(), 0, u64::MAX) }
+  }
+  /// Checks if the sync is signaled, without waiting.
+  #[inline]
+  pub fn get_sync_signaled(&self, sync: &SyncObject) -> bool {
+    let mut status = 0;
+    unsafe {
+      self.GetSynciv(sync.0.as_ptr(), GL_SYNC_STATUS, 1, null_mut(), &mut status)
+    };
+    status as GLenum == GL_SIGNALED
+  }
+  #[inline]
+  pub fn delete_sync(&self, sync: SyncObject) {
+    unsafe { self.DeleteSync(sync.0.as_ptr()) }
+  }
+}
+
+/// The non-error results of [`client_wait_sync`](EzGl::client_wait_sync).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum SyncWaitStatus {
+  /// The sync was signaled before the call.
+  AlreadySignaled = GL_ALREADY_SIGNALED,
+  /// The sync was signaled during the call.
+  ConditionSatisfied = GL_CONDITION_SATISFIED,
+  /// The sync wasn't signaled within the timeout.
+  TimeoutExpired = GL_TIMEOUT_EXPIRED,
+}
+impl SyncWaitStatus {
+  #[inline]
+  #[must_use]
+  pub const fn is_signaled(self) -> bool {
+    !matches!(self, Self::TimeoutExpired)
+  }
+}
 
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct VertexArrayObject(NonZeroU32);
 
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct SyncObject(NonNull<c_void>);
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct BufferObject(NonZeroU32);
@@ -674,6 +2871,7 @@ pub enum BufferTarget {
   DispatchIndirectBuffer = GL_DISPATCH_INDIRECT_BUFFER,
   DrawIndirectBuffer = GL_DRAW_INDIRECT_BUFFER,
   ElementArrayBuffer = GL_ELEMENT_ARRAY_BUFFER,
+  ParameterBuffer = GL_PARAMETER_BUFFER,
   PixelPackBuffer = GL_PIXEL_PACK_BUFFER,
   PixelUnpackBuffer = GL_PIXEL_UNPACK_BUFFER,
   QueryBuffer = GL_QUERY_BUFFER,
@@ -743,6 +2941,37 @@ pub enum MagFilter {
   Linear = GL_LINEAR,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Swizzle {
+  Red = GL_RED,
+  Green = GL_GREEN,
+  Blue = GL_BLUE,
+  Alpha = GL_ALPHA,
+  Zero = GL_ZERO,
+  One = GL_ONE,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum CompareFunc {
+  Never = GL_NEVER,
+  Less = GL_LESS,
+  Equal = GL_EQUAL,
+  LessEqual = GL_LEQUAL,
+  Greater = GL_GREATER,
+  NotEqual = GL_NOTEQUAL,
+  GreaterEqual = GL_GEQUAL,
+  Always = GL_ALWAYS,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DepthStencilTextureMode {
+  DepthComponent = GL_DEPTH_COMPONENT,
+  StencilIndex = GL_STENCIL_INDEX,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum BufferUsageHint {
@@ -757,7 +2986,7 @@ pub enum BufferUsageHint {
   DynamicCopy = GL_DYNAMIC_COPY,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum ShaderType {
   ComputeShader = GL_COMPUTE_SHADER,
@@ -801,3 +3030,61 @@ pub enum BlendFuncSeparate {
   Src1Alpha = GL_SRC1_ALPHA,
   OneMinusSrc1Alpha = GL_ONE_MINUS_SRC1_ALPHA,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn half_from_f32_boundaries() {
+    // The largest finite half, and what rounds to it or past it.
+    assert_eq!(Half::from_f32(65504.0).0, 0x7BFF);
+    assert_eq!(Half::from_f32(65519.0).0, 0x7BFF);
+    assert_eq!(Half::from_f32(65520.0).0, 0x7C00);
+    assert_eq!(Half::from_f32(-1.0e6).0, 0xFC00);
+    // The smallest normal and the subnormals below it.
+    assert_eq!(Half::from_f32(2.0f32.powi(-14)).0, 0x0400);
+    assert_eq!(Half::from_f32(2.0f32.powi(-24)).0, 0x0001);
+    assert_eq!(Half::from_f32(1.5 * 2.0f32.powi(-25)).0, 0x0001);
+    assert_eq!(Half::from_f32(2.0f32.powi(-26)).0, 0x0000);
+    assert_eq!(Half::from_f32(-0.0).0, 0x8000);
+  }
+
+  #[test]
+  fn half_from_f32_ties_to_even() {
+    // Halfway between 1.0 (even) and the next half up.
+    assert_eq!(Half::from_f32(1.0 + 2.0f32.powi(-11)).0, 0x3C00);
+    // Halfway between the next two, where the upper one is even.
+    assert_eq!(Half::from_f32(1.0 + 3.0 * 2.0f32.powi(-11)).0, 0x3C02);
+    // The same in the subnormal range: 0.5 and 1.5 times 2^-24.
+    assert_eq!(Half::from_f32(2.0f32.powi(-25)).0, 0x0000);
+    assert_eq!(Half::from_f32(3.0 * 2.0f32.powi(-25)).0, 0x0002);
+    // Just above a tie always rounds up.
+    assert_eq!(
+      Half::from_f32(f32::from_bits((1.0 + 2.0f32.powi(-11)).to_bits() + 1)).0,
+      0x3C01
+    );
+  }
+
+  #[test]
+  fn half_inf_and_nan() {
+    assert_eq!(Half::from_f32(f32::INFINITY).0, 0x7C00);
+    assert_eq!(Half::from_f32(f32::NEG_INFINITY).0, 0xFC00);
+    assert!(Half::from_f32(f32::NAN).to_f32().is_nan());
+    assert_eq!(Half(0x7C00).to_f32(), f32::INFINITY);
+    assert!(Half(0x7C01).to_f32().is_nan());
+  }
+
+  #[test]
+  fn half_round_trips_every_value() {
+    for bits in 0..=u16::MAX {
+      let h = Half(bits);
+      let f = h.to_f32();
+      if f.is_nan() {
+        assert!(Half::from_f32(f).to_f32().is_nan());
+      } else {
+        assert_eq!(Half::from_f32(f), h, "{bits:#06X} -> {f}");
+      }
+    }
+  }
+}
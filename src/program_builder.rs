@@ -0,0 +1,221 @@
+use super::*;
+
+/// How transform feedback varyings are written to buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum TransformFeedbackMode {
+  /// All varyings go into one buffer, one vertex after another.
+  Interleaved = GL_INTERLEAVED_ATTRIBS,
+  /// Each varying goes into its own buffer binding.
+  Separate = GL_SEPARATE_ATTRIBS,
+}
+
+/// Why [`ProgramBuilder::build`] failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramBuildError {
+  /// GL couldn't make a shader object for a stage.
+  CreateShader(ShaderType),
+  /// A stage didn't compile.
+  Compile { stage: ShaderType, log: Box<str> },
+  /// GL couldn't make a program object.
+  CreateProgram,
+  /// The stages compiled, but didn't link together.
+  Link { log: Box<str> },
+}
+impl core::fmt::Display for ProgramBuildError {
+  #[inline]
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::CreateShader(stage) => write!(f, "Couldn't create a new {stage:?} ID."),
+      Self::Compile { stage, log } => write!(f, "{stage:?} failed to compile:\n{log}"),
+      Self::CreateProgram => write!(f, "Couldn't create a new program ID."),
+      Self::Link { log } => write!(f, "Program failed to link:\n{log}"),
+    }
+  }
+}
+impl std::error::Error for ProgramBuildError {}
+impl ProgramBuildError {
+  /// The compile or link log, or the error message for other errors.
+  #[inline]
+  #[must_use]
+  pub fn into_log(self) -> Box<str> {
+    match self {
+      Self::Compile { log, .. } | Self::Link { log } => log,
+      other => other.to_string().into_boxed_str(),
+    }
+  }
+}
+
+/// Builds a program from any combination of shader stages.
+///
+/// ```no_run
+/// # use ezgl::*;
+/// # let gl = EzGl::new_boxed();
+/// # let (vertex_src, geometry_src, fragment_src) = ("", "", "");
+/// let program = ProgramBuilder::new()
+///   .stage(ShaderType::VertexShader, vertex_src)
+///   .stage(ShaderType::GeometryShader, geometry_src)
+///   .stage(ShaderType::FragmentShader, fragment_src)
+///   .attrib_location(0, "position")
+///   .build(&gl)
+///   .unwrap();
+/// ```
+///
+/// Every shader object made while building is deleted before `build`
+/// returns, whether it succeeds or not.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct ProgramBuilder<'a> {
+  stages: Vec<(ShaderType, &'a str)>,
+  attrib_locations: Vec<(GLuint, &'a str)>,
+  frag_data_locations: Vec<(GLuint, &'a str)>,
+  transform_feedback: Option<(&'a [&'a str], TransformFeedbackMode)>,
+  pub(crate) separable: bool,
+  binary_retrievable: bool,
+}
+impl<'a> ProgramBuilder<'a> {
+  #[inline]
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Adds a shader stage with the given source.
+  #[inline]
+  #[must_use]
+  pub fn stage(mut self, shader_type: ShaderType, src: &'a str) -> Self {
+    self.stages.push((shader_type, src));
+    self
+  }
+  /// Binds a vertex input to an attribute location, before linking.
+  ///
+  /// This is overridden by a `layout(location = N)` in the shader.
+  #[inline]
+  #[must_use]
+  pub fn attrib_location(mut self, index: GLuint, name: &'a str) -> Self {
+    self.attrib_locations.push((index, name));
+    self
+  }
+  /// Binds a fragment output to a draw buffer, before linking.
+  ///
+  /// This is overridden by a `layout(location = N)` in the shader. Not
+  /// available in GLES.
+  #[inline]
+  #[must_use]
+  pub fn frag_data_location(mut self, color_number: GLuint, name: &'a str) -> Self {
+    self.frag_data_locations.push((color_number, name));
+    self
+  }
+  /// Sets which outputs of the last vertex processing stage are captured with
+  /// transform feedback.
+  #[inline]
+  #[must_use]
+  pub fn transform_feedback_varyings(
+    mut self, varyings: &'a [&'a str], mode: TransformFeedbackMode,
+  ) -> Self {
+    self.transform_feedback = Some((varyings, mode));
+    self
+  }
+  /// Marks the program as separable, so that it can be used for only some
+  /// stages of a program pipeline.
+  #[inline]
+  #[must_use]
+  pub fn separable(mut self, separable: bool) -> Self {
+    self.separable = separable;
+    self
+  }
+
+  /// Hints that the program's binary will be read back with
+  /// [`get_program_binary`](EzGl::get_program_binary).
+  #[inline]
+  #[must_use]
+  pub fn binary_retrievable(mut self, retrievable: bool) -> Self {
+    self.binary_retrievable = retrievable;
+    self
+  }
+
+  /// Compiles every stage and links them into a new program.
+  #[inline]
+  pub fn build(&self, gl: &EzGl) -> Result<ProgramObject, ProgramBuildError> {
+    let mut shaders = Vec::with_capacity(self.stages.len());
+    let delete_all = |shaders: Vec<ShaderObject>| {
+      for shader in shaders {
+        gl.delete_shader(shader);
+      }
+    };
+    for &(stage, src) in self.stages.iter() {
+      let Ok(shader) = gl.create_shader(stage) else {
+        delete_all(shaders);
+        return Err(ProgramBuildError::CreateShader(stage));
+      };
+      gl.set_shader_source(&shader, src);
+      gl.compile_shader(&shader);
+      let compiled = gl.get_shader_compile_success(&shader);
+      shaders.push(shader);
+      if !compiled {
+        let log = gl.get_shader_info_log(shaders.last().unwrap());
+        delete_all(shaders);
+        return Err(ProgramBuildError::Compile { stage, log });
+      }
+    }
+    let Ok(program) = gl.create_program() else {
+      delete_all(shaders);
+      return Err(ProgramBuildError::CreateProgram);
+    };
+    for shader in shaders.iter() {
+      gl.attach_shader(&program, shader);
+    }
+    for &(index, name) in self.attrib_locations.iter() {
+      let name_z = format!("{name}\0");
+      unsafe {
+        gl.BindAttribLocation(program.0.get(), index, name_z.as_ptr().cast::<GLchar>())
+      };
+    }
+    for &(color_number, name) in self.frag_data_locations.iter() {
+      let name_z = format!("{name}\0");
+      unsafe {
+        gl.BindFragDataLocation(
+          program.0.get(),
+          color_number,
+          name_z.as_ptr().cast::<GLchar>(),
+        )
+      };
+    }
+    if let Some((varyings, mode)) = self.transform_feedback {
+      let names_z: Vec<String> =
+        varyings.iter().map(|name| format!("{name}\0")).collect();
+      let ptrs: Vec<*const GLchar> =
+        names_z.iter().map(|name_z| name_z.as_ptr().cast::<GLchar>()).collect();
+      unsafe {
+        gl.TransformFeedbackVaryings(
+          program.0.get(),
+          ptrs.len().try_into().unwrap(),
+          ptrs.as_ptr(),
+          mode as GLenum,
+        )
+      };
+    }
+    if self.separable {
+      gl.set_program_separable(&program, true);
+    }
+    if self.binary_retrievable {
+      unsafe {
+        gl.ProgramParameteri(
+          program.0.get(),
+          GL_PROGRAM_BINARY_RETRIEVABLE_HINT,
+          GL_TRUE as GLint,
+        )
+      };
+    }
+    gl.link_program(&program);
+    for shader in shaders {
+      gl.detach_shader(&program, &shader);
+      gl.delete_shader(shader);
+    }
+    if gl.get_program_link_success(&program) {
+      Ok(program)
+    } else {
+      let log = gl.get_program_info_log(&program);
+      gl.delete_program(program);
+      Err(ProgramBuildError::Link { log })
+    }
+  }
+}
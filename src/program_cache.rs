@@ -0,0 +1,285 @@
+use super::*;
+use std::{
+  hash::{Hash, Hasher},
+  path::{Path, PathBuf},
+};
+
+/// A linked program in the driver's own binary format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramBinary {
+  /// The driver specific binary format.
+  pub format: GLenum,
+  pub data: Vec<u8>,
+}
+
+/// Program binaries require GL 4.1 or GLES 3.0.
+impl EzGl {
+  /// The number of program binary formats the driver supports.
+  ///
+  /// If this is 0 then program binaries can't be used at all.
+  #[inline]
+  pub fn get_num_program_binary_formats(&self) -> u32 {
+    let mut out = 0;
+    unsafe { self.GetIntegerv(GL_NUM_PROGRAM_BINARY_FORMATS, &mut out) };
+    out.try_into().unwrap()
+  }
+  /// Reads back the binary of a linked program.
+  ///
+  /// For best results, set the
+  /// [`binary_retrievable`](ProgramBuilder::binary_retrievable) hint when
+  /// building the program.
+  ///
+  /// ## Failure
+  /// * The program has no binary (eg: it's not linked, or the driver doesn't
+  ///   support any binary formats).
+  #[inline]
+  pub fn get_program_binary(&self, program: &ProgramObject) -> Result<ProgramBinary, ()> {
+    let mut len = 0;
+    unsafe { self.GetProgramiv(program.0.get(), GL_PROGRAM_BINARY_LENGTH, &mut len) };
+    if len <= 0 {
+      return Err(());
+    }
+    let mut data: Vec<u8> = vec![0; len.try_into().unwrap()];
+    let mut bytes_written = 0;
+    let mut format = 0;
+    unsafe {
+      self.GetProgramBinary(
+        program.0.get(),
+        len,
+        &mut bytes_written,
+        &mut format,
+        data.as_mut_ptr().cast::<c_void>(),
+      )
+    };
+    if bytes_written <= 0 {
+      return Err(());
+    }
+    data.truncate(bytes_written.try_into().unwrap());
+    Ok(ProgramBinary { format, data })
+  }
+  /// Loads a binary from [`get_program_binary`](Self::get_program_binary) into
+  /// a program, which replaces any previous link of the program.
+  ///
+  /// Drivers will reject binaries made by another driver, GPU, or driver
+  /// version, so always be ready to compile the program from source instead.
+  ///
+  /// ## Failure
+  /// * The driver rejected the binary. The program is left unlinked.
+  #[inline]
+  pub fn program_binary(
+    &self, program: &ProgramObject, binary: &ProgramBinary,
+  ) -> Result<(), ()> {
+    unsafe {
+      self.ProgramBinary(
+        program.0.get(),
+        binary.format,
+        binary.data.as_ptr().cast::<c_void>(),
+        binary.data.len().try_into().unwrap(),
+      )
+    };
+    if self.get_program_link_success(program) {
+      Ok(())
+    } else {
+      Err(())
+    }
+  }
+}
+
+/// Stores program binaries in a directory, so that later runs can skip
+/// compiling shaders.
+///
+/// Each program is keyed by a hash of its [`ProgramBuilder`] (the stage
+/// sources and other settings) along with the GL vendor, renderer, and version
+/// strings. When a driver update makes the driver reject a stored binary, the
+/// program is compiled again and the stored binary is replaced.
+///
+/// The keys are 128-bit FNV-1a hashes, so they stay the same between builds
+/// and Rust versions. Each file starts with a header holding the full key,
+/// and a file with a bad header is treated as missing.
+#[derive(Debug, Clone)]
+pub struct ProgramCache {
+  dir: PathBuf,
+  driver_hash: u128,
+}
+impl ProgramCache {
+  /// Makes a cache that stores binaries in `dir`, creating the directory if
+  /// needed.
+  #[inline]
+  pub fn new(gl: &EzGl, dir: impl AsRef<Path>) -> std::io::Result<Self> {
+    let dir = dir.as_ref().to_path_buf();
+    std::fs::create_dir_all(&dir)?;
+    let mut hasher = Fnv1a128::new();
+    gl.get_vendor().hash(&mut hasher);
+    gl.get_renderer().hash(&mut hasher);
+    gl.get_version().hash(&mut hasher);
+    Ok(Self { dir, driver_hash: hasher.finish_u128() })
+  }
+  #[inline]
+  #[must_use]
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Loads the program's binary from the cache if possible, otherwise builds
+  /// the program and stores its binary.
+  ///
+  /// Problems with reading or writing the cache files are ignored, since the
+  /// program can always be built instead.
+  #[inline]
+  pub fn load_or_build(
+    &self, gl: &EzGl, builder: &ProgramBuilder<'_>,
+  ) -> Result<ProgramObject, ProgramBuildError> {
+    let key = self.key_for(builder);
+    let path = self.dir.join(format!("{key:032x}.bin"));
+    if let Some(binary) = read_binary_file(&path, key) {
+      let program = gl.create_program().map_err(|()| ProgramBuildError::CreateProgram)?;
+      if builder.separable {
+        gl.set_program_separable(&program, true);
+      }
+      if gl.program_binary(&program, &binary).is_ok() {
+        return Ok(program);
+      }
+      gl.delete_program(program);
+      std::fs::remove_file(&path).ok();
+    }
+    let program = builder.clone().binary_retrievable(true).build(gl)?;
+    if let Ok(binary) = gl.get_program_binary(&program) {
+      write_binary_file(&path, key, &binary).ok();
+    }
+    Ok(program)
+  }
+
+  /// Deletes every stored binary.
+  #[inline]
+  pub fn clear(&self) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(&self.dir)? {
+      let path = entry?.path();
+      if path.extension().is_some_and(|ext| ext == "bin") {
+        std::fs::remove_file(path)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn key_for(&self, builder: &ProgramBuilder<'_>) -> u128 {
+    let mut hasher = Fnv1a128::new();
+    self.driver_hash.hash(&mut hasher);
+    builder.hash(&mut hasher);
+    hasher.finish_u128()
+  }
+}
+
+/// 128-bit FNV-1a. Integers are hashed as little endian bytes, and `usize` as
+/// a `u64`, so the output is the same on every target.
+struct Fnv1a128(u128);
+impl Fnv1a128 {
+  const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+  const PRIME: u128 = 0x0000000001000000000000000000013b;
+  fn new() -> Self {
+    Self(Self::OFFSET_BASIS)
+  }
+  fn finish_u128(&self) -> u128 {
+    self.0
+  }
+}
+impl Hasher for Fnv1a128 {
+  fn finish(&self) -> u64 {
+    self.0 as u64
+  }
+  fn write(&mut self, bytes: &[u8]) {
+    for &b in bytes {
+      self.0 ^= u128::from(b);
+      self.0 = self.0.wrapping_mul(Self::PRIME);
+    }
+  }
+  fn write_u16(&mut self, i: u16) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_u32(&mut self, i: u32) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_u64(&mut self, i: u64) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_u128(&mut self, i: u128) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_usize(&mut self, i: usize) {
+    self.write_u64(i as u64)
+  }
+}
+
+// The file is a header, then the binary. The header is (all little endian):
+// * `FILE_MAGIC`
+// * `FILE_VERSION` as a u32
+// * the binary format as a u32
+// * the binary's length in bytes as a u64
+// * the key as a u128
+
+const FILE_MAGIC: [u8; 8] = *b"EZGLPBIN";
+const FILE_VERSION: u32 = 1;
+const HEADER_LEN: usize = 8 + 4 + 4 + 8 + 16;
+
+fn read_binary_file(path: &Path, key: u128) -> Option<ProgramBinary> {
+  let bytes = std::fs::read(path).ok()?;
+  let (magic, rest) = bytes.split_first_chunk::<8>()?;
+  let (version, rest) = rest.split_first_chunk::<4>()?;
+  let (format, rest) = rest.split_first_chunk::<4>()?;
+  let (len, rest) = rest.split_first_chunk::<8>()?;
+  let (file_key, data) = rest.split_first_chunk::<16>()?;
+  let header_ok = *magic == FILE_MAGIC
+    && u32::from_le_bytes(*version) == FILE_VERSION
+    && u64::from_le_bytes(*len) == data.len() as u64
+    && u128::from_le_bytes(*file_key) == key;
+  if !header_ok {
+    return None;
+  }
+  Some(ProgramBinary { format: GLenum::from_le_bytes(*format), data: data.to_vec() })
+}
+
+fn write_binary_file(
+  path: &Path, key: u128, binary: &ProgramBinary,
+) -> std::io::Result<()> {
+  let mut bytes = Vec::with_capacity(HEADER_LEN + binary.data.len());
+  bytes.extend_from_slice(&FILE_MAGIC);
+  bytes.extend_from_slice(&FILE_VERSION.to_le_bytes());
+  bytes.extend_from_slice(&binary.format.to_le_bytes());
+  bytes.extend_from_slice(&(binary.data.len() as u64).to_le_bytes());
+  bytes.extend_from_slice(&key.to_le_bytes());
+  bytes.extend_from_slice(&binary.data);
+  // Write to a temporary file first so that a crash can't leave a partial
+  // binary behind.
+  let tmp_path = path.with_extension("tmp");
+  std::fs::write(&tmp_path, bytes)?;
+  std::fs::rename(tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fnv1a128_matches_reference_values() {
+    assert_eq!(Fnv1a128::new().finish_u128(), Fnv1a128::OFFSET_BASIS);
+    let mut hasher = Fnv1a128::new();
+    hasher.write(b"a");
+    assert_eq!(hasher.finish_u128(), 0xd228cb696f1a8caf78912b704e4a8964);
+  }
+
+  #[test]
+  fn binary_file_header_rejects_wrong_key_and_truncation() {
+    let path = std::env::temp_dir().join(format!("ezgl_test_{}.bin", std::process::id()));
+    let binary = ProgramBinary { format: 0x1234, data: vec![1, 2, 3, 4, 5] };
+    write_binary_file(&path, 7, &binary).unwrap();
+    assert_eq!(read_binary_file(&path, 7), Some(binary.clone()));
+    // A different key is a miss.
+    assert_eq!(read_binary_file(&path, 8), None);
+    // So is a truncated file.
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert_eq!(read_binary_file(&path, 7), None);
+    std::fs::write(&path, &bytes[..HEADER_LEN - 1]).unwrap();
+    assert_eq!(read_binary_file(&path, 7), None);
+    std::fs::remove_file(&path).unwrap();
+  }
+}
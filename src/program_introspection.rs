@@ -0,0 +1,401 @@
+// The GL names for non-square matrix types have a lowercase `x` in them.
+#![allow(non_upper_case_globals)]
+
+use super::*;
+
+macro_rules! glsl_types {
+  ($($(#[$m:meta])* $v:ident = $c:ident,)*) => {
+    /// The type of a shader variable, as reported by program introspection.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum GlslType {
+      $($(#[$m])* $v,)*
+      /// A type not listed here, as its raw `GLenum` value.
+      Other(GLenum),
+    }
+    impl GlslType {
+      #[inline]
+      #[must_use]
+      pub const fn from_gl_enum(ty: GLenum) -> Self {
+        match ty {
+          $($c => Self::$v,)*
+          other => Self::Other(other),
+        }
+      }
+      #[inline]
+      #[must_use]
+      pub const fn to_gl_enum(self) -> GLenum {
+        match self {
+          $(Self::$v => $c,)*
+          Self::Other(other) => other,
+        }
+      }
+    }
+  };
+}
+
+glsl_types! {
+  Float = GL_FLOAT,
+  Vec2 = GL_FLOAT_VEC2,
+  Vec3 = GL_FLOAT_VEC3,
+  Vec4 = GL_FLOAT_VEC4,
+  Double = GL_DOUBLE,
+  DVec2 = GL_DOUBLE_VEC2,
+  DVec3 = GL_DOUBLE_VEC3,
+  DVec4 = GL_DOUBLE_VEC4,
+  Int = GL_INT,
+  IVec2 = GL_INT_VEC2,
+  IVec3 = GL_INT_VEC3,
+  IVec4 = GL_INT_VEC4,
+  UInt = GL_UNSIGNED_INT,
+  UVec2 = GL_UNSIGNED_INT_VEC2,
+  UVec3 = GL_UNSIGNED_INT_VEC3,
+  UVec4 = GL_UNSIGNED_INT_VEC4,
+  Bool = GL_BOOL,
+  BVec2 = GL_BOOL_VEC2,
+  BVec3 = GL_BOOL_VEC3,
+  BVec4 = GL_BOOL_VEC4,
+  Mat2 = GL_FLOAT_MAT2,
+  Mat3 = GL_FLOAT_MAT3,
+  Mat4 = GL_FLOAT_MAT4,
+  Mat2x3 = GL_FLOAT_MAT2x3,
+  Mat2x4 = GL_FLOAT_MAT2x4,
+  Mat3x2 = GL_FLOAT_MAT3x2,
+  Mat3x4 = GL_FLOAT_MAT3x4,
+  Mat4x2 = GL_FLOAT_MAT4x2,
+  Mat4x3 = GL_FLOAT_MAT4x3,
+  DMat2 = GL_DOUBLE_MAT2,
+  DMat3 = GL_DOUBLE_MAT3,
+  DMat4 = GL_DOUBLE_MAT4,
+  DMat2x3 = GL_DOUBLE_MAT2x3,
+  DMat2x4 = GL_DOUBLE_MAT2x4,
+  DMat3x2 = GL_DOUBLE_MAT3x2,
+  DMat3x4 = GL_DOUBLE_MAT3x4,
+  DMat4x2 = GL_DOUBLE_MAT4x2,
+  DMat4x3 = GL_DOUBLE_MAT4x3,
+  Sampler1D = GL_SAMPLER_1D,
+  Sampler2D = GL_SAMPLER_2D,
+  Sampler3D = GL_SAMPLER_3D,
+  SamplerCube = GL_SAMPLER_CUBE,
+  Sampler1DShadow = GL_SAMPLER_1D_SHADOW,
+  Sampler2DShadow = GL_SAMPLER_2D_SHADOW,
+  SamplerCubeShadow = GL_SAMPLER_CUBE_SHADOW,
+  Sampler1DArray = GL_SAMPLER_1D_ARRAY,
+  Sampler2DArray = GL_SAMPLER_2D_ARRAY,
+  Sampler2DArrayShadow = GL_SAMPLER_2D_ARRAY_SHADOW,
+  SamplerCubeArray = GL_SAMPLER_CUBE_MAP_ARRAY,
+  Sampler2DMultisample = GL_SAMPLER_2D_MULTISAMPLE,
+  SamplerBuffer = GL_SAMPLER_BUFFER,
+  ISampler2D = GL_INT_SAMPLER_2D,
+  ISampler3D = GL_INT_SAMPLER_3D,
+  ISamplerCube = GL_INT_SAMPLER_CUBE,
+  ISampler2DArray = GL_INT_SAMPLER_2D_ARRAY,
+  USampler2D = GL_UNSIGNED_INT_SAMPLER_2D,
+  USampler3D = GL_UNSIGNED_INT_SAMPLER_3D,
+  USamplerCube = GL_UNSIGNED_INT_SAMPLER_CUBE,
+  USampler2DArray = GL_UNSIGNED_INT_SAMPLER_2D_ARRAY,
+  Image2D = GL_IMAGE_2D,
+  Image3D = GL_IMAGE_3D,
+  ImageCube = GL_IMAGE_CUBE,
+  Image2DArray = GL_IMAGE_2D_ARRAY,
+  IImage2D = GL_INT_IMAGE_2D,
+  UImage2D = GL_UNSIGNED_INT_IMAGE_2D,
+  AtomicUInt = GL_UNSIGNED_INT_ATOMIC_COUNTER,
+}
+
+/// An active vertex input of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveAttribute {
+  pub name: Box<str>,
+  /// Built-in inputs such as `gl_VertexID` don't have a location.
+  pub location: Option<GLuint>,
+  pub ty: GlslType,
+  pub array_size: usize,
+}
+
+/// An active uniform of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveUniform {
+  /// Arrays are named by their first element, eg `lights[0]`.
+  pub name: Box<str>,
+  /// Uniforms within a block don't have a location.
+  pub location: Option<ShaderLocation>,
+  pub ty: GlslType,
+  pub array_size: usize,
+  /// The index of the uniform block this is part of, if any.
+  pub block_index: Option<GLuint>,
+  /// Byte offset within the block, if the uniform is part of a block.
+  pub offset: Option<usize>,
+  /// Bytes between array elements within a block, or 0.
+  pub array_stride: usize,
+  /// Bytes between matrix columns within a block, or 0.
+  pub matrix_stride: usize,
+}
+
+/// An active uniform block of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveUniformBlock {
+  pub name: Box<str>,
+  pub index: GLuint,
+  /// The uniform buffer binding point that the block reads from.
+  pub binding: GLuint,
+  /// The minimum size, in bytes, of a buffer range bound for this block.
+  pub data_size: usize,
+  /// The uniforms of the block, sorted by offset.
+  pub members: Vec<ActiveUniform>,
+}
+
+#[inline]
+fn opt_index(i: GLint) -> Option<GLuint> {
+  GLuint::try_from(i).ok()
+}
+
+impl EzGl {
+  /// Gets all active vertex inputs of a linked program.
+  ///
+  /// Uses the program interface query API when it's loaded (GL 4.3, GLES 3.1)
+  /// and `glGetActiveAttrib` otherwise.
+  #[inline]
+  pub fn get_active_attributes(&self, program: &ProgramObject) -> Vec<ActiveAttribute> {
+    if self.has_loaded().GetProgramResourceiv() {
+      let props = [GL_TYPE, GL_ARRAY_SIZE, GL_LOCATION];
+      (0..self.program_resource_count(program, GL_PROGRAM_INPUT))
+        .map(|index| {
+          let [ty, array_size, location] =
+            self.program_resource_props(program, GL_PROGRAM_INPUT, index, props);
+          ActiveAttribute {
+            name: self.program_resource_name(program, GL_PROGRAM_INPUT, index),
+            location: opt_index(location),
+            ty: GlslType::from_gl_enum(ty as GLenum),
+            array_size: array_size.try_into().unwrap(),
+          }
+        })
+        .collect()
+    } else {
+      let count = self.program_iv(program, GL_ACTIVE_ATTRIBUTES);
+      let max_len = self.program_iv(program, GL_ACTIVE_ATTRIBUTE_MAX_LENGTH);
+      (0..GLuint::try_from(count).unwrap())
+        .map(|index| {
+          let (name, array_size, ty) =
+            read_active_name(max_len, |buf_len, len, size, ty, buf| unsafe {
+              self.GetActiveAttrib(program.0.get(), index, buf_len, len, size, ty, buf)
+            });
+          let name_z = format!("{name}\0");
+          let location = unsafe {
+            self.GetAttribLocation(program.0.get(), name_z.as_ptr().cast::<GLchar>())
+          };
+          ActiveAttribute {
+            name,
+            location: opt_index(location),
+            ty: GlslType::from_gl_enum(ty),
+            array_size: array_size.try_into().unwrap(),
+          }
+        })
+        .collect()
+    }
+  }
+
+  /// Gets all active uniforms of a linked program, including those within
+  /// uniform blocks.
+  ///
+  /// Uses the program interface query API when it's loaded (GL 4.3, GLES 3.1)
+  /// and `glGetActiveUniform` otherwise.
+  #[inline]
+  pub fn get_active_uniforms(&self, program: &ProgramObject) -> Vec<ActiveUniform> {
+    if self.has_loaded().GetProgramResourceiv() {
+      let props = [
+        GL_TYPE,
+        GL_ARRAY_SIZE,
+        GL_LOCATION,
+        GL_BLOCK_INDEX,
+        GL_OFFSET,
+        GL_ARRAY_STRIDE,
+        GL_MATRIX_STRIDE,
+      ];
+      (0..self.program_resource_count(program, GL_UNIFORM))
+        .map(|index| {
+          let [ty, array_size, location, block_index, offset, array_stride, matrix_stride] =
+            self.program_resource_props(program, GL_UNIFORM, index, props);
+          let block_index = opt_index(block_index);
+          ActiveUniform {
+            name: self.program_resource_name(program, GL_UNIFORM, index),
+            location: (location != -1).then_some(ShaderLocation(location)),
+            ty: GlslType::from_gl_enum(ty as GLenum),
+            array_size: array_size.try_into().unwrap(),
+            block_index,
+            offset: block_index.and(offset.try_into().ok()),
+            array_stride: array_stride.try_into().unwrap_or(0),
+            matrix_stride: matrix_stride.try_into().unwrap_or(0),
+          }
+        })
+        .collect()
+    } else {
+      let count = self.program_iv(program, GL_ACTIVE_UNIFORMS);
+      let max_len = self.program_iv(program, GL_ACTIVE_UNIFORM_MAX_LENGTH);
+      let indices: Vec<GLuint> = (0..GLuint::try_from(count).unwrap()).collect();
+      let per_uniform = |pname: GLenum| -> Vec<GLint> {
+        let mut out = vec![0; indices.len()];
+        if !indices.is_empty() {
+          unsafe {
+            self.GetActiveUniformsiv(
+              program.0.get(),
+              count,
+              indices.as_ptr(),
+              pname,
+              out.as_mut_ptr(),
+            )
+          };
+        }
+        out
+      };
+      let block_indices = per_uniform(GL_UNIFORM_BLOCK_INDEX);
+      let offsets = per_uniform(GL_UNIFORM_OFFSET);
+      let array_strides = per_uniform(GL_UNIFORM_ARRAY_STRIDE);
+      let matrix_strides = per_uniform(GL_UNIFORM_MATRIX_STRIDE);
+      indices
+        .iter()
+        .map(|&index| {
+          let (name, array_size, ty) =
+            read_active_name(max_len, |buf_len, len, size, ty, buf| unsafe {
+              self.GetActiveUniform(program.0.get(), index, buf_len, len, size, ty, buf)
+            });
+          let i = index as usize;
+          let block_index = opt_index(block_indices[i]);
+          ActiveUniform {
+            location: if block_index.is_some() {
+              None
+            } else {
+              self.get_uniform_location(program, &name)
+            },
+            name,
+            ty: GlslType::from_gl_enum(ty),
+            array_size: array_size.try_into().unwrap(),
+            block_index,
+            offset: block_index.and(offsets[i].try_into().ok()),
+            array_stride: array_strides[i].try_into().unwrap_or(0),
+            matrix_stride: matrix_strides[i].try_into().unwrap_or(0),
+          }
+        })
+        .collect()
+    }
+  }
+
+  /// Gets all active uniform blocks of a linked program, along with their
+  /// members.
+  #[inline]
+  pub fn get_active_uniform_blocks(
+    &self, program: &ProgramObject,
+  ) -> Vec<ActiveUniformBlock> {
+    let mut uniforms = self.get_active_uniforms(program);
+    uniforms.sort_by_key(|u| u.offset);
+    let count = self.program_iv(program, GL_ACTIVE_UNIFORM_BLOCKS);
+    (0..GLuint::try_from(count).unwrap())
+      .map(|index| {
+        let block_iv = |pname: GLenum| {
+          let mut out = 0;
+          unsafe {
+            self.GetActiveUniformBlockiv(program.0.get(), index, pname, &mut out)
+          };
+          out
+        };
+        let name_len = block_iv(GL_UNIFORM_BLOCK_NAME_LENGTH);
+        let mut name_buf: Vec<u8> = vec![0; name_len.try_into().unwrap()];
+        let mut written = 0;
+        unsafe {
+          self.GetActiveUniformBlockName(
+            program.0.get(),
+            index,
+            name_len,
+            &mut written,
+            name_buf.as_mut_ptr().cast::<GLchar>(),
+          )
+        };
+        name_buf.truncate(written.try_into().unwrap());
+        ActiveUniformBlock {
+          name: String::from_utf8_lossy(&name_buf).into(),
+          index,
+          binding: block_iv(GL_UNIFORM_BLOCK_BINDING).try_into().unwrap(),
+          data_size: block_iv(GL_UNIFORM_BLOCK_DATA_SIZE).try_into().unwrap(),
+          members: uniforms
+            .iter()
+            .filter(|u| u.block_index == Some(index))
+            .cloned()
+            .collect(),
+        }
+      })
+      .collect()
+  }
+
+  fn program_iv(&self, program: &ProgramObject, pname: GLenum) -> GLint {
+    let mut out = 0;
+    unsafe { self.GetProgramiv(program.0.get(), pname, &mut out) };
+    out
+  }
+
+  fn program_resource_count(&self, program: &ProgramObject, interface: GLenum) -> GLuint {
+    let mut out = 0;
+    unsafe {
+      self.GetProgramInterfaceiv(
+        program.0.get(),
+        interface,
+        GL_ACTIVE_RESOURCES,
+        &mut out,
+      )
+    };
+    out.try_into().unwrap()
+  }
+
+  fn program_resource_props<const N: usize>(
+    &self, program: &ProgramObject, interface: GLenum, index: GLuint, props: [GLenum; N],
+  ) -> [GLint; N] {
+    let mut out = [0; N];
+    unsafe {
+      self.GetProgramResourceiv(
+        program.0.get(),
+        interface,
+        index,
+        N as GLsizei,
+        props.as_ptr(),
+        N as GLsizei,
+        null_mut(),
+        out.as_mut_ptr(),
+      )
+    };
+    out
+  }
+
+  fn program_resource_name(
+    &self, program: &ProgramObject, interface: GLenum, index: GLuint,
+  ) -> Box<str> {
+    let [name_len] =
+      self.program_resource_props(program, interface, index, [GL_NAME_LENGTH]);
+    let mut name_buf: Vec<u8> = vec![0; name_len.try_into().unwrap()];
+    let mut written = 0;
+    unsafe {
+      self.GetProgramResourceName(
+        program.0.get(),
+        interface,
+        index,
+        name_len,
+        &mut written,
+        name_buf.as_mut_ptr().cast::<GLchar>(),
+      )
+    };
+    name_buf.truncate(written.try_into().unwrap());
+    String::from_utf8_lossy(&name_buf).into()
+  }
+}
+
+/// Calls a `glGetActiveAttrib`/`glGetActiveUniform` style function, returning
+/// the name, array size, and type.
+fn read_active_name(
+  max_len: GLint,
+  f: impl FnOnce(GLsizei, *mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar),
+) -> (Box<str>, GLint, GLenum) {
+  let mut name_buf: Vec<u8> = vec![0; max_len.try_into().unwrap()];
+  let mut written = 0;
+  let mut size = 0;
+  let mut ty = 0;
+  f(max_len, &mut written, &mut size, &mut ty, name_buf.as_mut_ptr().cast::<GLchar>());
+  name_buf.truncate(written.try_into().unwrap());
+  (String::from_utf8_lossy(&name_buf).into(), size, ty)
+}
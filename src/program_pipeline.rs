@@ -0,0 +1,174 @@
+use super::*;
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ProgramPipelineObject(NonZeroU32);
+
+/// The shader stages of a program to use in a program pipeline, for
+/// [`use_program_stages`](EzGl::use_program_stages).
+///
+/// Combine flags with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct ProgramStages(GLbitfield);
+impl ProgramStages {
+  pub const VERTEX: Self = Self(GL_VERTEX_SHADER_BIT);
+  pub const TESS_CONTROL: Self = Self(GL_TESS_CONTROL_SHADER_BIT);
+  pub const TESS_EVALUATION: Self = Self(GL_TESS_EVALUATION_SHADER_BIT);
+  pub const GEOMETRY: Self = Self(GL_GEOMETRY_SHADER_BIT);
+  pub const FRAGMENT: Self = Self(GL_FRAGMENT_SHADER_BIT);
+  pub const COMPUTE: Self = Self(GL_COMPUTE_SHADER_BIT);
+  /// Every stage that the program has.
+  pub const ALL: Self = Self(GL_ALL_SHADER_BITS);
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.0 & other.0) == other.0
+  }
+}
+impl core::ops::BitOr for ProgramStages {
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    Self(self.0 | rhs.0)
+  }
+}
+impl core::ops::BitOrAssign for ProgramStages {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    self.0 |= rhs.0
+  }
+}
+impl From<ShaderType> for ProgramStages {
+  #[inline]
+  fn from(shader_type: ShaderType) -> Self {
+    match shader_type {
+      ShaderType::ComputeShader => Self::COMPUTE,
+      ShaderType::VertexShader => Self::VERTEX,
+      ShaderType::TessControlShader => Self::TESS_CONTROL,
+      ShaderType::TessEvaluationShader => Self::TESS_EVALUATION,
+      ShaderType::GeometryShader => Self::GEOMETRY,
+      ShaderType::FragmentShader => Self::FRAGMENT,
+    }
+  }
+}
+
+/// Program pipelines require GL 4.1 or GLES 3.1.
+impl EzGl {
+  /// Sets if a program can be used for only some stages of a program pipeline.
+  ///
+  /// This must be set before the program is linked. A [`ProgramBuilder`] can
+  /// also do this, with [`separable`](ProgramBuilder::separable).
+  #[inline]
+  pub fn set_program_separable(&self, program: &ProgramObject, separable: bool) {
+    unsafe {
+      self.ProgramParameteri(
+        program.0.get(),
+        GL_PROGRAM_SEPARABLE,
+        if separable { GL_TRUE } else { GL_FALSE } as GLint,
+      )
+    }
+  }
+  #[inline]
+  pub fn get_program_separable(&self, program: &ProgramObject) -> bool {
+    let mut out = 0;
+    unsafe { self.GetProgramiv(program.0.get(), GL_PROGRAM_SEPARABLE, &mut out) };
+    out != 0
+  }
+
+  #[inline]
+  pub fn gen_program_pipeline(&self) -> Result<ProgramPipelineObject, ()> {
+    let mut obj = 0;
+    unsafe { self.GenProgramPipelines(1, &mut obj) };
+    NonZeroU32::new(obj).ok_or(()).map(ProgramPipelineObject)
+  }
+  #[inline]
+  pub fn delete_program_pipeline(&self, pipeline: ProgramPipelineObject) {
+    unsafe { self.DeleteProgramPipelines(1, &pipeline.0.get()) }
+  }
+  /// Binds a program pipeline for rendering.
+  ///
+  /// The pipeline is only used while no program is current, so this also
+  /// clears the current program.
+  #[inline]
+  pub fn bind_program_pipeline(&self, pipeline: &ProgramPipelineObject) {
+    unsafe {
+      self.UseProgram(0);
+      self.BindProgramPipeline(pipeline.0.get());
+    }
+  }
+  #[inline]
+  pub fn clear_program_pipeline_binding(&self) {
+    unsafe { self.BindProgramPipeline(0) }
+  }
+  /// Sets the pipeline to use the given stages from a separable program.
+  ///
+  /// Any stage of the pipeline that was using another program is replaced.
+  #[inline]
+  pub fn use_program_stages(
+    &self, pipeline: &ProgramPipelineObject, stages: ProgramStages,
+    program: &ProgramObject,
+  ) {
+    unsafe { self.UseProgramStages(pipeline.0.get(), stages.0, program.0.get()) }
+  }
+  /// Clears the given stages of the pipeline, so they have no program.
+  #[inline]
+  pub fn clear_program_stages(
+    &self, pipeline: &ProgramPipelineObject, stages: ProgramStages,
+  ) {
+    unsafe { self.UseProgramStages(pipeline.0.get(), stages.0, 0) }
+  }
+  /// Sets which program of the pipeline the plain `set_uniform` calls affect.
+  ///
+  /// Using [`set_program_uniform`](Self::set_program_uniform) is usually
+  /// clearer.
+  #[inline]
+  pub fn set_active_shader_program(
+    &self, pipeline: &ProgramPipelineObject, program: &ProgramObject,
+  ) {
+    unsafe { self.ActiveShaderProgram(pipeline.0.get(), program.0.get()) }
+  }
+  /// Checks if the pipeline can run with the current GL state.
+  ///
+  /// ## Failure
+  /// * The error is the pipeline's info log, which says what's wrong (eg: the
+  ///   outputs of one stage don't match the inputs of the next).
+  #[inline]
+  pub fn validate_program_pipeline(
+    &self, pipeline: &ProgramPipelineObject,
+  ) -> Result<(), Box<str>> {
+    let mut valid = 0;
+    unsafe {
+      self.ValidateProgramPipeline(pipeline.0.get());
+      self.GetProgramPipelineiv(pipeline.0.get(), GL_VALIDATE_STATUS, &mut valid);
+    }
+    if valid != 0 {
+      Ok(())
+    } else {
+      Err(self.get_program_pipeline_info_log(pipeline))
+    }
+  }
+  #[inline]
+  pub fn get_program_pipeline_info_log(
+    &self, pipeline: &ProgramPipelineObject,
+  ) -> Box<str> {
+    let mut len = 0;
+    unsafe { self.GetProgramPipelineiv(pipeline.0.get(), GL_INFO_LOG_LENGTH, &mut len) }
+    if len == 0 {
+      String::new().into_boxed_str()
+    } else {
+      let mut v: Vec<u8> = Vec::with_capacity(len.try_into().unwrap());
+      let mut bytes_written = 0;
+      unsafe {
+        self.GetProgramPipelineInfoLog(
+          pipeline.0.get(),
+          v.capacity().try_into().unwrap(),
+          &mut bytes_written,
+          v.as_mut_ptr().cast::<GLchar>(),
+        );
+        v.set_len(bytes_written.try_into().unwrap());
+      }
+      String::from_utf8_lossy(&v).into_owned().into_boxed_str()
+    }
+  }
+}
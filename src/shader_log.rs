@@ -0,0 +1,366 @@
+use super::*;
+
+/// How bad a [`ShaderDiagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DiagnosticSeverity {
+  Info,
+  Warning,
+  Error,
+}
+impl DiagnosticSeverity {
+  fn from_word(word: &str) -> Option<Self> {
+    match word.to_ascii_lowercase().as_str() {
+      "error" | "fatal error" => Some(Self::Error),
+      "warning" => Some(Self::Warning),
+      "info" | "note" => Some(Self::Info),
+      _ => None,
+    }
+  }
+}
+
+/// One message from a shader compile log.
+///
+/// Use [`parse_shader_log`] to get these from a log, or
+/// [`ProcessedShader::diagnostics`] to also map them back to the original
+/// source files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+  pub severity: DiagnosticSeverity,
+  /// The source file, once the diagnostic has been mapped back with a
+  /// [`LineMap`].
+  pub file: Option<Box<str>>,
+  /// The line number, starting at 1.
+  pub line: Option<u32>,
+  /// The column number, starting at 1. Only some drivers give this.
+  pub column: Option<u32>,
+  pub message: Box<str>,
+  /// The text of the line that the diagnostic points at, if known.
+  pub source_line: Option<Box<str>>,
+}
+
+/// Parses a shader info log into diagnostics.
+///
+/// This understands the formats used by Mesa (`0:12(5): error: ...`, with or
+/// without the column), NVIDIA
+/// (`0(12) : error C0000: ...`), and AMD, Intel, and ANGLE
+/// (`ERROR: 0:12: ...`). A log line in none of these formats is added to the
+/// message of the diagnostic before it, since drivers sometimes spread one
+/// message over several lines.
+///
+/// The line numbers are those of the source that was given to GL.
+#[inline]
+#[must_use]
+pub fn parse_shader_log(log: &str) -> Vec<ShaderDiagnostic> {
+  let mut out: Vec<ShaderDiagnostic> = Vec::new();
+  for log_line in log.lines() {
+    let log_line = log_line.trim_end_matches(['\0', ' ', '\t', '\r']);
+    if log_line.trim().is_empty() {
+      continue;
+    }
+    if let Some(diagnostic) = parse_log_line(log_line) {
+      out.push(diagnostic);
+    } else if let Some(last) = out.last_mut() {
+      last.message = format!("{}\n{}", last.message, log_line.trim()).into_boxed_str();
+    } else {
+      out.push(ShaderDiagnostic {
+        severity: DiagnosticSeverity::Info,
+        file: None,
+        line: None,
+        column: None,
+        message: log_line.trim().into(),
+        source_line: None,
+      });
+    }
+  }
+  out
+}
+
+fn parse_log_line(log_line: &str) -> Option<ShaderDiagnostic> {
+  let diagnostic = |severity, line, column, message: &str| ShaderDiagnostic {
+    severity,
+    file: None,
+    line,
+    column,
+    message: message.trim().into(),
+    source_line: None,
+  };
+
+  // AMD, Intel, ANGLE: `ERROR: 0:12: message`
+  if let Some((word, rest)) = log_line.split_once(": ") {
+    if let Some(severity) = DiagnosticSeverity::from_word(word) {
+      let located = take_number(rest)
+        .and_then(|(_, rest)| take_number(rest.strip_prefix(':')?))
+        .and_then(|(line, rest)| Some((line, rest.strip_prefix(':')?)));
+      return Some(match located {
+        Some((line, message)) => diagnostic(severity, Some(line), None, message),
+        None => diagnostic(severity, None, None, rest),
+      });
+    }
+  }
+
+  // Mesa: `0:12(5): error: message`, or `0:12: error: message`.
+  if let Some(rest) = take_number(log_line).and_then(|(_, rest)| rest.strip_prefix(':')) {
+    if let Some((line, rest)) = take_number(rest) {
+      let column = rest
+        .strip_prefix('(')
+        .and_then(take_number)
+        .and_then(|(column, rest)| Some((column, rest.strip_prefix(')')?)));
+      let (column, rest) = match column {
+        Some((column, rest)) => (Some(column), rest),
+        None => (None, rest),
+      };
+      let located =
+        rest.strip_prefix(':').and_then(|rest| rest.split_once(':')).and_then(
+          |(word, message)| Some((DiagnosticSeverity::from_word(word.trim())?, message)),
+        );
+      if let Some((severity, message)) = located {
+        return Some(diagnostic(severity, Some(line), column, message));
+      }
+    }
+  }
+
+  // NVIDIA: `0(12) : error C0000: message`, sometimes without the leading 0.
+  let rest = take_number(log_line).map_or(log_line, |(_, rest)| rest);
+  let (line, rest) = take_number(rest.strip_prefix('(')?)?;
+  let rest = rest.strip_prefix(')')?.trim_start().strip_prefix(':')?.trim_start();
+  let word_end = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+  let severity = DiagnosticSeverity::from_word(&rest[..word_end])?;
+  Some(diagnostic(severity, Some(line), None, &rest[word_end..]))
+}
+
+/// Splits a leading decimal number off of a string.
+fn take_number(s: &str) -> Option<(u32, &str)> {
+  let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+  Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+impl ProcessedShader {
+  /// Joins `(name, source)` chunks into one shader, mapping each line back to
+  /// its chunk.
+  ///
+  /// This is for shaders that are put together without a
+  /// [`ShaderPreprocessor`], so that [`diagnostics`](Self::diagnostics) still
+  /// works on them. Each chunk gets a newline after it.
+  #[inline]
+  #[must_use]
+  pub fn from_chunks(chunks: &[(&str, &str)]) -> Self {
+    let mut out = Self::default();
+    for (name, src) in chunks {
+      for (line, line_number) in src.lines().zip(1..) {
+        out.source.push_str(line);
+        out.source.push('\n');
+        out.line_map.push(name, line_number);
+      }
+    }
+    out
+  }
+
+  /// Parses a compile log for this shader's source, and maps each diagnostic
+  /// back to the file and line it came from.
+  ///
+  /// Diagnostics that point at lines the preprocessor wrote itself get the
+  /// file name [`GENERATED_SOURCE_NAME`].
+  #[inline]
+  #[must_use]
+  pub fn diagnostics(&self, log: &str) -> Vec<ShaderDiagnostic> {
+    let mut out = parse_shader_log(log);
+    for diagnostic in out.iter_mut() {
+      let Some(line) = diagnostic.line else { continue };
+      if let Some((file, orig_line)) = self.line_map.lookup(line) {
+        diagnostic.source_line =
+          self.source.lines().nth(usize::try_from(line).unwrap() - 1).map(Box::from);
+        diagnostic.file = Some(file.into());
+        diagnostic.line = Some(orig_line);
+      }
+    }
+    out
+  }
+}
+
+impl EzGl {
+  /// Parses a shader's info log with [`parse_shader_log`].
+  #[inline]
+  pub fn get_shader_diagnostics(&self, shader: &ShaderObject) -> Vec<ShaderDiagnostic> {
+    parse_shader_log(&self.get_shader_info_log(shader))
+  }
+}
+
+/// Shows the diagnostic along with its source line and a caret, eg:
+///
+/// ```text
+/// error: 'colour' : undeclared identifier
+///   --> lighting.glsl:12:3
+///    |
+/// 12 |   colour = vec4(1.0);
+///    |   ^
+/// ```
+///
+/// Without a column, the whole line is underlined.
+impl core::fmt::Display for ShaderDiagnostic {
+  #[inline]
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let severity = match self.severity {
+      DiagnosticSeverity::Info => "info",
+      DiagnosticSeverity::Warning => "warning",
+      DiagnosticSeverity::Error => "error",
+    };
+    let mut message_lines = self.message.lines();
+    writeln!(f, "{severity}: {}", message_lines.next().unwrap_or(""))?;
+    for message_line in message_lines {
+      writeln!(f, "{message_line}")?;
+    }
+    let Some(line) = self.line else { return Ok(()) };
+    let file = self.file.as_deref().unwrap_or("<shader>");
+    let gutter = line.to_string().len();
+    match self.column {
+      Some(column) => writeln!(f, "{:gutter$}--> {file}:{line}:{column}", "")?,
+      None => writeln!(f, "{:gutter$}--> {file}:{line}", "")?,
+    }
+    let Some(source_line) = self.source_line.as_deref() else { return Ok(()) };
+    writeln!(f, "{:gutter$} |", "")?;
+    writeln!(f, "{line} | {source_line}")?;
+    let (start, len) = match self.column {
+      Some(column) => (usize::try_from(column).unwrap().saturating_sub(1), 1),
+      None => {
+        let trimmed = source_line.trim();
+        (source_line.len() - source_line.trim_start().len(), trimmed.len().max(1))
+      }
+    };
+    // Keep any tabs before the caret, so it lines up however tabs are shown.
+    let padding: String = source_line
+      .chars()
+      .take(start)
+      .map(|c| if c == '\t' { '\t' } else { ' ' })
+      .collect();
+    writeln!(f, "{:gutter$} | {padding}{}", "", "^".repeat(len))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn shader() -> ProcessedShader {
+    ProcessedShader::from_chunks(&[
+      ("common.glsl", "#version 330 core\nuniform float time;"),
+      ("main.frag", "out vec4 color;\nvoid main() {\n  colour = vec4(time);\n}"),
+    ])
+  }
+
+  #[track_caller]
+  fn check(
+    d: &ShaderDiagnostic, severity: DiagnosticSeverity, file: &str, line: u32,
+    message: &str,
+  ) {
+    assert_eq!(d.severity, severity);
+    assert_eq!(d.file.as_deref(), Some(file));
+    assert_eq!(d.line, Some(line));
+    assert_eq!(&*d.message, message);
+  }
+
+  #[test]
+  fn chunk_lines_map_to_their_chunk() {
+    let shader = shader();
+    assert_eq!(shader.line_map.len(), 6);
+    assert_eq!(shader.line_map.lookup(2), Some(("common.glsl", 2)));
+    assert_eq!(shader.line_map.lookup(5), Some(("main.frag", 3)));
+    assert_eq!(shader.source.lines().nth(4), Some("  colour = vec4(time);"));
+  }
+
+  #[test]
+  fn mesa_lines_map_to_chunk_and_column() {
+    let log = "0:5(3): error: `colour' undeclared\n\
+      0:5(3): error: value of type vec4 cannot be assigned to variable of type error\n\
+      0:3: warning: extension `GL_ARB_foo' unsupported\n";
+    let d = shader().diagnostics(log);
+    assert_eq!(d.len(), 3);
+    check(&d[0], DiagnosticSeverity::Error, "main.frag", 3, "`colour' undeclared");
+    assert_eq!(d[0].column, Some(3));
+    assert_eq!(d[0].source_line.as_deref(), Some("  colour = vec4(time);"));
+    check(
+      &d[1],
+      DiagnosticSeverity::Error,
+      "main.frag",
+      3,
+      "value of type vec4 cannot be assigned to variable of type error",
+    );
+    check(
+      &d[2],
+      DiagnosticSeverity::Warning,
+      "main.frag",
+      1,
+      "extension `GL_ARB_foo' unsupported",
+    );
+    assert_eq!(d[2].column, None);
+  }
+
+  #[test]
+  fn nvidia_lines_map_to_chunk_with_error_codes() {
+    let log = "0(5) : error C1008: undefined variable \"colour\"\n\
+      0(2) : warning C7533: global variable time is deprecated after version 120\n";
+    let d = shader().diagnostics(log);
+    assert_eq!(d.len(), 2);
+    check(
+      &d[0],
+      DiagnosticSeverity::Error,
+      "main.frag",
+      3,
+      "C1008: undefined variable \"colour\"",
+    );
+    check(
+      &d[1],
+      DiagnosticSeverity::Warning,
+      "common.glsl",
+      2,
+      "C7533: global variable time is deprecated after version 120",
+    );
+  }
+
+  #[test]
+  fn amd_lines_map_to_chunk_and_summary_has_no_line() {
+    let log = "ERROR: 0:5: 'colour' : undeclared identifier \n\
+      ERROR: 0:5: 'assign' :  cannot convert from '4-component vector of float' to 'float'\n\
+      ERROR: 2 compilation errors.  No code generated.\n\0";
+    let d = shader().diagnostics(log);
+    assert_eq!(d.len(), 3);
+    check(
+      &d[0],
+      DiagnosticSeverity::Error,
+      "main.frag",
+      3,
+      "'colour' : undeclared identifier",
+    );
+    check(
+      &d[1],
+      DiagnosticSeverity::Error,
+      "main.frag",
+      3,
+      "'assign' :  cannot convert from '4-component vector of float' to 'float'",
+    );
+    assert_eq!(d[2].severity, DiagnosticSeverity::Error);
+    assert_eq!(d[2].line, None);
+    assert_eq!(&*d[2].message, "2 compilation errors.  No code generated.");
+  }
+
+  #[test]
+  fn angle_warnings_and_errors_map_to_chunk() {
+    let log = "WARNING: 0:2: 'time' : unused uniform\n\
+      ERROR: 0:5: 'colour' : undeclared identifier\n";
+    let d = shader().diagnostics(log);
+    assert_eq!(d.len(), 2);
+    check(
+      &d[0],
+      DiagnosticSeverity::Warning,
+      "common.glsl",
+      2,
+      "'time' : unused uniform",
+    );
+    check(
+      &d[1],
+      DiagnosticSeverity::Error,
+      "main.frag",
+      3,
+      "'colour' : undeclared identifier",
+    );
+  }
+}
@@ -0,0 +1,458 @@
+use super::*;
+use std::{
+  borrow::Borrow,
+  collections::HashMap,
+  hash::Hash,
+  path::{Path, PathBuf},
+};
+
+/// The GLSL flavor and version to write shaders for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderTarget {
+  /// If this is GLSL ES.
+  pub es: bool,
+  /// The version as it appears in a `#version` line, eg: 410 or 310.
+  pub version: u32,
+}
+impl ShaderTarget {
+  /// The `#version` line for this target, without a newline.
+  #[inline]
+  #[must_use]
+  pub fn version_line(self) -> String {
+    if self.es && self.version >= 300 {
+      format!("#version {} es", self.version)
+    } else if !self.es && self.version >= 150 {
+      format!("#version {} core", self.version)
+    } else {
+      format!("#version {}", self.version)
+    }
+  }
+}
+
+impl EzGl {
+  /// Works out the best [`ShaderTarget`] for the current context, from the
+  /// `GL_VERSION` and `GL_SHADING_LANGUAGE_VERSION` strings.
+  ///
+  /// ## Failure
+  /// * The shading language version string couldn't be parsed.
+  #[inline]
+  pub fn get_shader_target(&self) -> Result<ShaderTarget, ()> {
+    let es = self.get_version().starts_with("OpenGL ES");
+    let glsl_version = self.get_shading_language_version();
+    // Examples: "4.60 NVIDIA", "OpenGL ES GLSL ES 3.10", "1.00"
+    let number = glsl_version
+      .split_whitespace()
+      .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+      .ok_or(())?;
+    let (major, minor) = number.split_once('.').ok_or(())?;
+    let major: u32 = major.parse().map_err(|_| ())?;
+    let minor: u32 = minor.get(..2).unwrap_or(minor).parse().map_err(|_| ())?;
+    Ok(ShaderTarget { es, version: major * 100 + minor })
+  }
+}
+
+/// A place that `#include` directives can read files from.
+///
+/// This is implemented for maps from name to source (a virtual file system),
+/// and for [`Path`] / [`PathBuf`] (a directory on disk that names are
+/// relative to).
+pub trait ShaderIncludes {
+  /// Gets the source for an include name, if there's a file with that name.
+  fn read_include(&self, name: &str) -> Option<String>;
+}
+impl<K, V> ShaderIncludes for HashMap<K, V>
+where
+  K: Borrow<str> + Hash + Eq,
+  V: AsRef<str>,
+{
+  #[inline]
+  fn read_include(&self, name: &str) -> Option<String> {
+    self.get(name).map(|src| src.as_ref().to_string())
+  }
+}
+impl ShaderIncludes for Path {
+  #[inline]
+  fn read_include(&self, name: &str) -> Option<String> {
+    std::fs::read_to_string(self.join(name)).ok()
+  }
+}
+impl ShaderIncludes for PathBuf {
+  #[inline]
+  fn read_include(&self, name: &str) -> Option<String> {
+    self.as_path().read_include(name)
+  }
+}
+
+/// Why [`ShaderPreprocessor::process`] failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreprocessError {
+  /// An `#include` line didn't have a quoted name.
+  BadInclude { file: Box<str>, line: u32 },
+  /// An `#include` named a file that couldn't be read.
+  IncludeNotFound { file: Box<str>, line: u32, name: Box<str> },
+  /// A file ended up including itself.
+  IncludeCycle { file: Box<str>, line: u32, name: Box<str> },
+}
+impl core::fmt::Display for PreprocessError {
+  #[inline]
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::BadInclude { file, line } => {
+        write!(f, "{file}:{line}: `#include` needs a quoted file name")
+      }
+      Self::IncludeNotFound { file, line, name } => {
+        write!(f, "{file}:{line}: couldn't find include file `{name}`")
+      }
+      Self::IncludeCycle { file, line, name } => {
+        write!(f, "{file}:{line}: `{name}` ends up including itself")
+      }
+    }
+  }
+}
+impl std::error::Error for PreprocessError {}
+
+/// The name that [`LineMap`] gives the lines that the preprocessor writes
+/// itself (`#version`, precision, and `#define` lines).
+pub const GENERATED_SOURCE_NAME: &str = "<generated>";
+
+/// Maps each line of a preprocessed shader back to the file and line it came
+/// from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineMap {
+  // One entry per output line: (index into `files`, line number in that file).
+  lines: Vec<(usize, u32)>,
+  files: Vec<Box<str>>,
+}
+impl LineMap {
+  /// Looks up the original file name and line number of an output line.
+  ///
+  /// Line numbers start at 1, as in compiler logs.
+  #[inline]
+  #[must_use]
+  pub fn lookup(&self, line: u32) -> Option<(&str, u32)> {
+    let &(file, orig_line) =
+      self.lines.get(usize::try_from(line).ok()?.checked_sub(1)?)?;
+    Some((&self.files[file], orig_line))
+  }
+  /// The number of output lines.
+  #[inline]
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.lines.len()
+  }
+  #[inline]
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.lines.is_empty()
+  }
+  pub(crate) fn push(&mut self, file: &str, line: u32) {
+    let file = match self.files.iter().position(|f| &**f == file) {
+      Some(i) => i,
+      None => {
+        self.files.push(file.into());
+        self.files.len() - 1
+      }
+    };
+    self.lines.push((file, line));
+  }
+}
+
+/// The output of [`ShaderPreprocessor::process`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessedShader {
+  /// The full source, ready to pass to GL.
+  pub source: String,
+  pub line_map: LineMap,
+}
+
+/// Assembles shader source for a [`ShaderTarget`].
+///
+/// The output starts with the `#version` line for the target, then any
+/// `#define`s. After that comes the source, with `#include "name"` lines replaced by the named
+/// file's contents. Any `#version` line in the source is dropped. Lines inside
+/// `/* ... */` comments are copied as they are, even if they look like
+/// directives.
+///
+/// For GLSL ES the default `float` precision is written just before the first
+/// line of the source that isn't a directive or a comment. GLSL ES needs
+/// `#extension` lines to come before any code, so this keeps the source's
+/// leading `#extension` lines valid.
+///
+/// ```no_run
+/// # use ezgl::*;
+/// # let gl = EzGl::new_boxed();
+/// # let fragment_src = "";
+/// let target = gl.get_shader_target().unwrap();
+/// let mut preprocessor = ShaderPreprocessor::new(target);
+/// preprocessor.define("MAX_LIGHTS", "8");
+/// let fragment = preprocessor.process("lit.frag", fragment_src).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct ShaderPreprocessor<'a> {
+  target: ShaderTarget,
+  float_precision: &'a str,
+  defines: Vec<(&'a str, &'a str)>,
+  includes: Option<&'a dyn ShaderIncludes>,
+}
+impl core::fmt::Debug for ShaderPreprocessor<'_> {
+  #[inline]
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("ShaderPreprocessor")
+      .field("target", &self.target)
+      .field("float_precision", &self.float_precision)
+      .field("defines", &self.defines)
+      .field("includes", &self.includes.is_some())
+      .finish()
+  }
+}
+impl<'a> ShaderPreprocessor<'a> {
+  #[inline]
+  #[must_use]
+  pub fn new(target: ShaderTarget) -> Self {
+    Self { target, float_precision: "mediump", defines: Vec::new(), includes: None }
+  }
+  #[inline]
+  #[must_use]
+  pub fn target(&self) -> ShaderTarget {
+    self.target
+  }
+  /// Sets the default `float` precision used for GLSL ES (`mediump` unless
+  /// set).
+  #[inline]
+  pub fn set_float_precision(&mut self, precision: &'a str) {
+    self.float_precision = precision;
+  }
+  /// Adds a `#define name value` line.
+  #[inline]
+  pub fn define(&mut self, name: &'a str, value: &'a str) {
+    self.defines.push((name, value));
+  }
+  /// Sets where `#include` reads files from.
+  ///
+  /// Include names are looked up exactly as written, not relative to the file
+  /// doing the including.
+  #[inline]
+  pub fn set_includes(&mut self, includes: &'a dyn ShaderIncludes) {
+    self.includes = Some(includes);
+  }
+
+  /// Processes a shader's source. `name` is what the line map calls `src`.
+  #[inline]
+  pub fn process(
+    &self, name: &str, src: &str,
+  ) -> Result<ProcessedShader, PreprocessError> {
+    let mut out = ProcessedShader::default();
+    let mut generated_line = 0;
+    let mut push_generated = |out: &mut ProcessedShader, line: &str| {
+      generated_line += 1;
+      out.source.push_str(line);
+      out.source.push('\n');
+      out.line_map.push(GENERATED_SOURCE_NAME, generated_line);
+    };
+    push_generated(&mut out, &self.target.version_line());
+    for (define_name, value) in self.defines.iter() {
+      push_generated(&mut out, &format!("#define {define_name} {value}"));
+    }
+    let mut precision = self.target.es.then(|| {
+      (format!("precision {} float;", self.float_precision), generated_line + 1)
+    });
+    let mut include_stack = vec![Box::from(name)];
+    self.process_file(name, src, &mut include_stack, &mut precision, &mut out)?;
+    // Source with no code at all still gets the precision.
+    push_precision(&mut precision, &mut out);
+    Ok(out)
+  }
+
+  /// `precision` is the precision line and its generated line number, until
+  /// it's been written.
+  fn process_file(
+    &self, name: &str, src: &str, include_stack: &mut Vec<Box<str>>,
+    precision: &mut Option<(String, u32)>, out: &mut ProcessedShader,
+  ) -> Result<(), PreprocessError> {
+    let mut in_block_comment = false;
+    for (line, line_number) in src.lines().zip(1..) {
+      // A `#` inside a block comment is just comment text.
+      let starts_in_comment = in_block_comment;
+      let (has_code, ends_in_comment) = scan_comments(line, in_block_comment);
+      in_block_comment = ends_in_comment;
+      let directive =
+        if starts_in_comment { None } else { line.trim_start().strip_prefix('#') };
+      let Some(directive) = directive else {
+        if has_code {
+          push_precision(precision, out);
+        }
+        out.source.push_str(line);
+        out.source.push('\n');
+        out.line_map.push(name, line_number);
+        continue;
+      };
+      let directive = directive.trim_start();
+      if directive.starts_with("version") {
+        continue;
+      }
+      let Some(include) = directive.strip_prefix("include") else {
+        out.source.push_str(line);
+        out.source.push('\n');
+        out.line_map.push(name, line_number);
+        continue;
+      };
+      let include_name = include
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.split_once('"'))
+        .map(|(include_name, _)| include_name)
+        .ok_or_else(|| PreprocessError::BadInclude {
+          file: name.into(),
+          line: line_number,
+        })?;
+      if include_stack.iter().any(|n| &**n == include_name) {
+        return Err(PreprocessError::IncludeCycle {
+          file: name.into(),
+          line: line_number,
+          name: include_name.into(),
+        });
+      }
+      let include_src = self
+        .includes
+        .and_then(|i| i.read_include(include_name))
+        .ok_or_else(|| PreprocessError::IncludeNotFound {
+          file: name.into(),
+          line: line_number,
+          name: include_name.into(),
+        })?;
+      include_stack.push(include_name.into());
+      self.process_file(include_name, &include_src, include_stack, precision, out)?;
+      include_stack.pop();
+    }
+    Ok(())
+  }
+}
+
+/// Writes the precision line, if it hasn't been written yet.
+fn push_precision(precision: &mut Option<(String, u32)>, out: &mut ProcessedShader) {
+  if let Some((line, generated_line)) = precision.take() {
+    out.source.push_str(&line);
+    out.source.push('\n');
+    out.line_map.push(GENERATED_SOURCE_NAME, generated_line);
+  }
+}
+
+/// Given whether a line starts inside a `/* ... */` comment, returns if the
+/// line has anything other than comments and whitespace, and if it ends inside
+/// a `/* ... */` comment.
+fn scan_comments(line: &str, mut in_comment: bool) -> (bool, bool) {
+  let mut has_code = false;
+  let mut rest = line;
+  loop {
+    if in_comment {
+      let Some(end) = rest.find("*/") else { return (has_code, true) };
+      rest = &rest[end + 2..];
+      in_comment = false;
+    } else {
+      let code_end = match (rest.find("/*"), rest.find("//")) {
+        (Some(start), Some(line_comment)) if line_comment < start => line_comment,
+        (Some(start), _) => start,
+        (None, line_comment) => line_comment.unwrap_or(rest.len()),
+      };
+      has_code |= !rest[..code_end].trim().is_empty();
+      if !rest[code_end..].starts_with("/*") {
+        return (has_code, false);
+      }
+      rest = &rest[code_end + 2..];
+      in_comment = true;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TARGET: ShaderTarget = ShaderTarget { es: false, version: 330 };
+
+  #[test]
+  fn nested_includes_map_lines_to_their_files() {
+    let includes: HashMap<&str, &str> =
+      [("a.glsl", "float a;\n#include \"b.glsl\"\nfloat a2;"), ("b.glsl", "float b;")]
+        .into_iter()
+        .collect();
+    let mut preprocessor = ShaderPreprocessor::new(TARGET);
+    preprocessor.set_includes(&includes);
+    let out = preprocessor
+      .process("main.frag", "#version 330\n#include \"a.glsl\"\nvoid main() {}")
+      .unwrap();
+    assert_eq!(
+      out.source,
+      "#version 330 core\nfloat a;\nfloat b;\nfloat a2;\nvoid main() {}\n"
+    );
+    assert_eq!(out.line_map.len(), 5);
+    assert_eq!(out.line_map.lookup(0), None);
+    assert_eq!(out.line_map.lookup(1), Some((GENERATED_SOURCE_NAME, 1)));
+    assert_eq!(out.line_map.lookup(2), Some(("a.glsl", 1)));
+    assert_eq!(out.line_map.lookup(3), Some(("b.glsl", 1)));
+    assert_eq!(out.line_map.lookup(4), Some(("a.glsl", 3)));
+    assert_eq!(out.line_map.lookup(5), Some(("main.frag", 3)));
+    assert_eq!(out.line_map.lookup(6), None);
+  }
+
+  #[test]
+  fn include_cycles_are_errors() {
+    let includes: HashMap<&str, &str> =
+      [("a.glsl", "#include \"b.glsl\""), ("b.glsl", "\n#include \"a.glsl\"")]
+        .into_iter()
+        .collect();
+    let mut preprocessor = ShaderPreprocessor::new(TARGET);
+    preprocessor.set_includes(&includes);
+    assert_eq!(
+      preprocessor.process("main.frag", "#include \"a.glsl\""),
+      Err(PreprocessError::IncludeCycle {
+        file: "b.glsl".into(),
+        line: 2,
+        name: "a.glsl".into()
+      })
+    );
+    assert!(matches!(
+      preprocessor.process("a.glsl", "#include \"a.glsl\""),
+      Err(PreprocessError::IncludeCycle { .. })
+    ));
+  }
+
+  #[test]
+  fn directives_in_block_comments_are_copied() {
+    let preprocessor = ShaderPreprocessor::new(TARGET);
+    let src = "/* old:\n#version 120\n#include \"gone.glsl\"\n*/ // /*\n#define X 1";
+    let out = preprocessor.process("main.frag", src).unwrap();
+    assert_eq!(
+      out.source,
+      "#version 330 core\n/* old:\n#version 120\n#include \"gone.glsl\"\n*/ // /*\n#define X 1\n"
+    );
+  }
+
+  #[test]
+  fn es_precision_goes_after_leading_extensions() {
+    let mut preprocessor =
+      ShaderPreprocessor::new(ShaderTarget { es: true, version: 300 });
+    preprocessor.define("X", "1");
+    let src = "#version 300 es\n\
+      #extension GL_OES_texture_3D : enable\n\
+      /* a\n\
+      comment */ // and another\n\
+      out vec4 color;";
+    let out = preprocessor.process("main.frag", src).unwrap();
+    assert_eq!(
+      out.source,
+      "#version 300 es\n#define X 1\n#extension GL_OES_texture_3D : enable\n\
+      /* a\ncomment */ // and another\nprecision mediump float;\nout vec4 color;\n"
+    );
+    assert_eq!(out.line_map.lookup(3), Some(("main.frag", 2)));
+    assert_eq!(out.line_map.lookup(6), Some((GENERATED_SOURCE_NAME, 3)));
+    assert_eq!(out.line_map.lookup(7), Some(("main.frag", 5)));
+  }
+
+  #[test]
+  fn es_precision_is_written_without_any_code() {
+    let preprocessor = ShaderPreprocessor::new(ShaderTarget { es: true, version: 310 });
+    let out = preprocessor.process("empty.frag", "// nothing").unwrap();
+    assert_eq!(out.source, "#version 310 es\n// nothing\nprecision mediump float;\n");
+  }
+}
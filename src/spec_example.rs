@@ -0,0 +1,58 @@
+// The example block from the `std140` rules in the GL spec (section
+// 7.6.2.2), shared by the `std140` and `std430` layout tests.
+
+use super::*;
+
+#[derive(Std140, Std430, Debug, PartialEq)]
+pub(crate) struct F {
+  pub(crate) d: i32,
+  pub(crate) e: [bool; 2],
+}
+#[derive(Std140, Std430, Debug, PartialEq)]
+pub(crate) struct O {
+  pub(crate) j: [u32; 3],
+  pub(crate) k: [f32; 2],
+  pub(crate) l: GlslArray<f32, 2>,
+  pub(crate) m: [f32; 2],
+  pub(crate) n: GlslArray<[[f32; 3]; 3], 2>,
+}
+#[derive(Std140, Std430, Debug, PartialEq)]
+pub(crate) struct Example {
+  pub(crate) a: f32,
+  pub(crate) b: [f32; 2],
+  pub(crate) c: [f32; 3],
+  pub(crate) f: F,
+  pub(crate) g: f32,
+  pub(crate) h: GlslArray<f32, 2>,
+  pub(crate) i: [[f32; 3]; 2],
+  pub(crate) o: GlslArray<O, 2>,
+}
+
+/// An `Example` where every value is different, so tests can find each one in
+/// the output bytes.
+///
+/// The `O` values count up from 100.0 and 200.0: `k` starts at the base,
+/// `l` at base + 2, `m` at base + 4, and the columns of `n` are base + 6, then
+/// base + 7 to base + 9.
+pub(crate) fn example() -> Example {
+  let o = |base: f32| O {
+    j: [1, 2, 3],
+    k: [base, base + 1.0],
+    l: GlslArray([base + 2.0, base + 3.0]),
+    m: [base + 4.0, base + 5.0],
+    n: GlslArray([
+      [[base + 6.0; 3]; 3],
+      [[base + 7.0; 3], [base + 8.0; 3], [base + 9.0; 3]],
+    ]),
+  };
+  Example {
+    a: 1.0,
+    b: [2.0, 3.0],
+    c: [4.0, 5.0, 6.0],
+    f: F { d: 7, e: [true, false] },
+    g: 8.0,
+    h: GlslArray([9.0, 10.0]),
+    i: [[11.0, 12.0, 13.0], [14.0, 15.0, 16.0]],
+    o: GlslArray([o(100.0), o(200.0)]),
+  }
+}
@@ -0,0 +1,377 @@
+use super::*;
+
+/// Types that can be written to a buffer with the GLSL `std140` layout.
+///
+/// * `f32`, `i32`, `u32`, and `bool` are the GLSL scalars of those types.
+/// * `[T; N]` of those (with `N` from 2 to 4) is the matching `vecN` (`ivecN`,
+///   `uvecN`, `bvecN`).
+/// * `[[f32; R]; C]` is a column-major matrix of `C` columns and `R` rows.
+/// * [`GlslArray<T, N>`](GlslArray) is a GLSL array, `T[N]`.
+///
+/// With the `derive` feature this can be derived for structs, which follow
+/// the `std140` rules for structs.
+///
+/// The layout rules are in section 7.6.2.2 of the GL 4.6 spec ("Standard
+/// Uniform Block Layout").
+pub trait Std140 {
+  /// The base alignment of the type, in bytes.
+  const STD140_ALIGN: usize;
+  /// The size of the type, in bytes.
+  const STD140_SIZE: usize;
+  /// Writes the value into `out`, which is exactly `STD140_SIZE` bytes.
+  ///
+  /// Padding bytes are left unchanged.
+  fn write_std140(&self, out: &mut [u8]);
+
+  /// Writes the value into a new, zero padded, `Vec`.
+  #[inline]
+  fn to_std140_bytes(&self) -> Vec<u8> {
+    let mut out = vec![0; Self::STD140_SIZE];
+    self.write_std140(&mut out);
+    out
+  }
+}
+
+/// A GLSL array, `T[N]`.
+///
+/// This is a separate type from `[T; N]`, which is used for GLSL vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct GlslArray<T, const N: usize>(pub [T; N]);
+impl<T: Default + Copy, const N: usize> Default for GlslArray<T, N> {
+  #[inline]
+  fn default() -> Self {
+    Self([T::default(); N])
+  }
+}
+
+/// Rounds `x` up to a multiple of `align`, which must be a power of two.
+#[inline]
+#[must_use]
+#[doc(hidden)]
+pub const fn layout_align_up(x: usize, align: usize) -> usize {
+  (x + (align - 1)) & !(align - 1)
+}
+
+/// The `(align, size)` of a `std140` struct with members of the given
+/// `(align, size)`, in order. Used by the derive.
+#[inline]
+#[must_use]
+#[doc(hidden)]
+pub const fn std140_struct_layout(members: &[(usize, usize)]) -> (usize, usize) {
+  let mut align = 16;
+  let mut size = 0;
+  let mut i = 0;
+  while i < members.len() {
+    let (member_align, member_size) = members[i];
+    if member_align > align {
+      align = member_align;
+    }
+    size = layout_align_up(size, member_align) + member_size;
+    i += 1;
+  }
+  (align, layout_align_up(size, align))
+}
+
+macro_rules! impl_std140_scalar {
+  ($($t:ty),*) => {$(
+    impl Std140 for $t {
+      const STD140_ALIGN: usize = 4;
+      const STD140_SIZE: usize = 4;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        out.copy_from_slice(&self.to_ne_bytes());
+      }
+    }
+    impl Std140 for [$t; 2] {
+      const STD140_ALIGN: usize = 8;
+      const STD140_SIZE: usize = 8;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+    }
+    impl Std140 for [$t; 3] {
+      const STD140_ALIGN: usize = 16;
+      const STD140_SIZE: usize = 12;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+    }
+    impl Std140 for [$t; 4] {
+      const STD140_ALIGN: usize = 16;
+      const STD140_SIZE: usize = 16;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+    }
+  )*};
+}
+impl_std140_scalar!(f32, i32, u32);
+
+#[inline]
+fn write_components<T: Std140>(components: &[T], out: &mut [u8]) {
+  for (c, chunk) in components.iter().zip(out.chunks_exact_mut(4)) {
+    c.write_std140(chunk);
+  }
+}
+
+// GLSL bools are 4 bytes in a buffer.
+impl Std140 for bool {
+  const STD140_ALIGN: usize = 4;
+  const STD140_SIZE: usize = 4;
+  #[inline]
+  fn write_std140(&self, out: &mut [u8]) {
+    u32::from(*self).write_std140(out)
+  }
+}
+impl<const N: usize> Std140 for [bool; N]
+where
+  [u32; N]: Std140,
+{
+  const STD140_ALIGN: usize = <[u32; N]>::STD140_ALIGN;
+  const STD140_SIZE: usize = <[u32; N]>::STD140_SIZE;
+  #[inline]
+  fn write_std140(&self, out: &mut [u8]) {
+    self.map(u32::from).write_std140(out)
+  }
+}
+
+// Arrays round the element stride up to a multiple of 16.
+impl<T: Std140, const N: usize> Std140 for GlslArray<T, N> {
+  const STD140_ALIGN: usize = layout_align_up(T::STD140_ALIGN, 16);
+  const STD140_SIZE: usize = N * layout_align_up(T::STD140_SIZE, Self::STD140_ALIGN);
+  #[inline]
+  fn write_std140(&self, out: &mut [u8]) {
+    let stride = layout_align_up(T::STD140_SIZE, Self::STD140_ALIGN);
+    for (t, chunk) in self.0.iter().zip(out.chunks_mut(stride)) {
+      t.write_std140(&mut chunk[..T::STD140_SIZE]);
+    }
+  }
+}
+
+// Matrices are laid out as an array of their column vectors.
+macro_rules! impl_std140_matrix {
+  ($($c:literal),*) => {$(
+    impl<const R: usize> Std140 for [[f32; R]; $c]
+    where
+      [f32; R]: Std140,
+    {
+      const STD140_ALIGN: usize = 16;
+      const STD140_SIZE: usize = $c * 16;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        for (column, chunk) in self.iter().zip(out.chunks_mut(16)) {
+          column.write_std140(&mut chunk[..<[f32; R]>::STD140_SIZE]);
+        }
+      }
+    }
+  )*};
+}
+impl_std140_matrix!(2, 3, 4);
+
+/// Implements `Std140` for a type by viewing it as another type with the same
+/// memory layout.
+#[allow(unused_macros)]
+macro_rules! impl_std140_as {
+  ($($t:ty => $as_t:ty),* $(,)?) => {$(
+    impl Std140 for $t {
+      const STD140_ALIGN: usize = <$as_t>::STD140_ALIGN;
+      const STD140_SIZE: usize = <$as_t>::STD140_SIZE;
+      #[inline]
+      fn write_std140(&self, out: &mut [u8]) {
+        const _: () = assert!(size_of::<$t>() == size_of::<$as_t>());
+        unsafe { &*(self as *const $t).cast::<$as_t>() }.write_std140(out)
+      }
+    }
+  )*};
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+  use super::*;
+  use mint::*;
+  impl_std140_as! {
+    Vector2<f32> => [f32; 2], Vector3<f32> => [f32; 3], Vector4<f32> => [f32; 4],
+    Vector2<i32> => [i32; 2], Vector3<i32> => [i32; 3], Vector4<i32> => [i32; 4],
+    Vector2<u32> => [u32; 2], Vector3<u32> => [u32; 3], Vector4<u32> => [u32; 4],
+    ColumnMatrix2<f32> => [[f32; 2]; 2],
+    ColumnMatrix3<f32> => [[f32; 3]; 3],
+    ColumnMatrix4<f32> => [[f32; 4]; 4],
+  }
+}
+
+#[cfg(feature = "ultraviolet")]
+mod ultraviolet_impls {
+  use super::*;
+  use ultraviolet::*;
+  impl_std140_as! {
+    Vec2 => [f32; 2], Vec3 => [f32; 3], Vec4 => [f32; 4],
+    Mat2 => [[f32; 2]; 2], Mat3 => [[f32; 3]; 3], Mat4 => [[f32; 4]; 4],
+  }
+}
+
+#[cfg(feature = "glam")]
+mod glam_impls {
+  use super::*;
+  use glam::*;
+  impl_std140_as! {
+    Vec2 => [f32; 2], Vec3 => [f32; 3], Vec4 => [f32; 4],
+    IVec2 => [i32; 2], IVec3 => [i32; 3], IVec4 => [i32; 4],
+    UVec2 => [u32; 2], UVec3 => [u32; 3], UVec4 => [u32; 4],
+    Mat2 => [[f32; 2]; 2], Mat3 => [[f32; 3]; 3], Mat4 => [[f32; 4]; 4],
+  }
+}
+
+impl EzGl {
+  /// Gets the index of a uniform block by name.
+  #[inline]
+  pub fn get_uniform_block_index(
+    &self, program: &ProgramObject, name: &str,
+  ) -> Option<GLuint> {
+    let name_z = format!("{name}\0");
+    let r = unsafe {
+      self.GetUniformBlockIndex(program.0.get(), name_z.as_ptr().cast::<GLchar>())
+    };
+    if r != GL_INVALID_INDEX {
+      Some(r)
+    } else {
+      None
+    }
+  }
+  /// Sets the uniform buffer binding point that a uniform block reads from.
+  ///
+  /// Use [`bind_buffer_base`](Self::bind_buffer_base) or
+  /// [`bind_buffer_range`](Self::bind_buffer_range) with
+  /// [`UniformBuffer`](BufferTarget::UniformBuffer) to attach a buffer to the
+  /// binding point. Programs that use the same binding point share the data.
+  #[inline]
+  pub fn set_uniform_block_binding(
+    &self, program: &ProgramObject, block_index: GLuint, binding: GLuint,
+  ) {
+    unsafe { self.UniformBlockBinding(program.0.get(), block_index, binding) }
+  }
+  /// Checks that `T` has the same `std140` size as a uniform block of the
+  /// program.
+  ///
+  /// The block should be declared with `layout(std140)`, and `T` should have
+  /// the same members in the same order. Matching sizes don't prove that the
+  /// members match, but it does catch most mistakes.
+  ///
+  /// ## Failure
+  /// * The program has no active uniform block with that name.
+  /// * The size reported by GL is not the size of `T`.
+  #[inline]
+  pub fn verify_std140_block<T: Std140>(
+    &self, program: &ProgramObject, block_name: &str,
+  ) -> Result<(), ()> {
+    let block_index = self.get_uniform_block_index(program, block_name).ok_or(())?;
+    let mut data_size = 0;
+    unsafe {
+      self.GetActiveUniformBlockiv(
+        program.0.get(),
+        block_index,
+        GL_UNIFORM_BLOCK_DATA_SIZE,
+        &mut data_size,
+      )
+    };
+    if usize::try_from(data_size) == Ok(T::STD140_SIZE) {
+      Ok(())
+    } else {
+      Err(())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+  }
+
+  #[test]
+  fn vector_layouts() {
+    assert_eq!((<f32>::STD140_ALIGN, <f32>::STD140_SIZE), (4, 4));
+    assert_eq!((<[f32; 2]>::STD140_ALIGN, <[f32; 2]>::STD140_SIZE), (8, 8));
+    // vec3 is aligned like a vec4, but only 12 bytes big.
+    assert_eq!((<[f32; 3]>::STD140_ALIGN, <[f32; 3]>::STD140_SIZE), (16, 12));
+    assert_eq!((<[bool; 3]>::STD140_ALIGN, <[bool; 3]>::STD140_SIZE), (16, 12));
+  }
+
+  #[test]
+  fn array_stride_rounds_up_to_16() {
+    type Floats = GlslArray<f32, 2>;
+    assert_eq!((Floats::STD140_ALIGN, Floats::STD140_SIZE), (16, 32));
+    let bytes = GlslArray([1.0f32, 2.0]).to_std140_bytes();
+    assert_eq!((f32_at(&bytes, 0), f32_at(&bytes, 16)), (1.0, 2.0));
+    type Vec3s = GlslArray<[f32; 3], 3>;
+    assert_eq!((Vec3s::STD140_ALIGN, Vec3s::STD140_SIZE), (16, 48));
+  }
+
+  #[test]
+  fn matrix_columns_are_16_bytes_apart() {
+    // mat2, mat2x3, mat3
+    assert_eq!(<[[f32; 2]; 2]>::STD140_SIZE, 32);
+    assert_eq!(<[[f32; 3]; 2]>::STD140_SIZE, 32);
+    assert_eq!(<[[f32; 3]; 3]>::STD140_SIZE, 48);
+    let bytes = [[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]].to_std140_bytes();
+    assert_eq!(bytes.len(), 48);
+    assert_eq!((f32_at(&bytes, 0), f32_at(&bytes, 8)), (1.0, 3.0));
+    assert_eq!((f32_at(&bytes, 16), f32_at(&bytes, 32)), (4.0, 7.0));
+    assert_eq!(f32_at(&bytes, 12), 0.0);
+  }
+
+  #[test]
+  fn struct_layout_rounds_to_16() {
+    // struct { int d; bvec2 e; }
+    assert_eq!(std140_struct_layout(&[(4, 4), (8, 8)]), (16, 16));
+    // struct { vec3 a; float b; } packs b into the vec3's padding.
+    assert_eq!(std140_struct_layout(&[(16, 12), (4, 4)]), (16, 16));
+    // struct { float a; vec3 b; }
+    assert_eq!(std140_struct_layout(&[(4, 4), (16, 12)]), (16, 32));
+  }
+
+  /// The example block from the `std140` rules in the GL spec, with its
+  /// offsets.
+  #[cfg(feature = "derive")]
+  mod spec_example {
+    use super::*;
+    use crate::spec_example::{example, Example, F, O};
+
+    #[test]
+    fn sizes() {
+      assert_eq!((F::STD140_ALIGN, F::STD140_SIZE), (16, 16));
+      assert_eq!((O::STD140_ALIGN, O::STD140_SIZE), (16, 176));
+      assert_eq!((Example::STD140_ALIGN, Example::STD140_SIZE), (16, 480));
+    }
+
+    #[test]
+    fn offsets() {
+      let bytes = example().to_std140_bytes();
+      let i32_at =
+        |offset: usize| i32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+      assert_eq!(f32_at(&bytes, 0), 1.0);
+      assert_eq!(f32_at(&bytes, 8), 2.0);
+      assert_eq!(f32_at(&bytes, 16), 4.0);
+      assert_eq!(i32_at(32), 7);
+      assert_eq!((i32_at(40), i32_at(44)), (1, 0));
+      assert_eq!(f32_at(&bytes, 48), 8.0);
+      assert_eq!((f32_at(&bytes, 64), f32_at(&bytes, 80)), (9.0, 10.0));
+      assert_eq!((f32_at(&bytes, 96), f32_at(&bytes, 112)), (11.0, 14.0));
+      for (base, start) in [(100.0, 128), (200.0, 304)] {
+        assert_eq!(i32_at(start), 1);
+        assert_eq!(f32_at(&bytes, start + 16), base);
+        assert_eq!(f32_at(&bytes, start + 32), base + 2.0);
+        assert_eq!(f32_at(&bytes, start + 48), base + 3.0);
+        assert_eq!(f32_at(&bytes, start + 64), base + 4.0);
+        assert_eq!(f32_at(&bytes, start + 80), base + 6.0);
+        assert_eq!(f32_at(&bytes, start + 128), base + 7.0);
+        assert_eq!(f32_at(&bytes, start + 160), base + 9.0);
+      }
+    }
+  }
+}
@@ -0,0 +1,469 @@
+use super::*;
+
+/// Types that can be written to (and read from) a buffer with the GLSL
+/// `std430` layout.
+///
+/// This covers the same types as [`Std140`], and the layout is the same
+/// except that arrays and structs aren't padded out to 16 byte alignment.
+/// `std430` is the default (and only) layout for shader storage blocks.
+///
+/// With the `derive` feature this can be derived for structs, which follow
+/// the `std430` rules for structs.
+pub trait Std430: Sized {
+  /// The base alignment of the type, in bytes.
+  const STD430_ALIGN: usize;
+  /// The size of the type, in bytes.
+  const STD430_SIZE: usize;
+  /// Writes the value into `out`, which is exactly `STD430_SIZE` bytes.
+  ///
+  /// Padding bytes are left unchanged.
+  fn write_std430(&self, out: &mut [u8]);
+  /// Reads a value from `bytes`, which is exactly `STD430_SIZE` bytes.
+  fn read_std430(bytes: &[u8]) -> Self;
+
+  /// Writes the value into a new, zero padded, `Vec`.
+  #[inline]
+  fn to_std430_bytes(&self) -> Vec<u8> {
+    let mut out = vec![0; Self::STD430_SIZE];
+    self.write_std430(&mut out);
+    out
+  }
+}
+
+/// The `(align, size)` of a `std430` struct with members of the given
+/// `(align, size)`, in order. Used by the derive.
+#[inline]
+#[must_use]
+#[doc(hidden)]
+pub const fn std430_struct_layout(members: &[(usize, usize)]) -> (usize, usize) {
+  let mut align = 1;
+  let mut size = 0;
+  let mut i = 0;
+  while i < members.len() {
+    let (member_align, member_size) = members[i];
+    if member_align > align {
+      align = member_align;
+    }
+    size = layout_align_up(size, member_align) + member_size;
+    i += 1;
+  }
+  (align, layout_align_up(size, align))
+}
+
+/// The distance in bytes between elements of a `std430` array of `T`.
+#[inline]
+#[must_use]
+pub const fn std430_array_stride<T: Std430>() -> usize {
+  layout_align_up(T::STD430_SIZE, T::STD430_ALIGN)
+}
+
+/// The byte offset of a runtime-sized array of `T` that follows a fixed
+/// header `H` in a shader storage block.
+///
+/// This treats the header as a struct, so its size is rounded up to its
+/// alignment. Either declare the block as `{ Header header; T items[]; }`,
+/// or make sure that the header's fields fill the header exactly.
+#[inline]
+#[must_use]
+pub const fn std430_trailing_array_offset<H: Std430, T: Std430>() -> usize {
+  layout_align_up(H::STD430_SIZE, T::STD430_ALIGN)
+}
+
+macro_rules! impl_std430_scalar {
+  ($($t:ty),*) => {$(
+    impl Std430 for $t {
+      const STD430_ALIGN: usize = 4;
+      const STD430_SIZE: usize = 4;
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        out.copy_from_slice(&self.to_ne_bytes());
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        Self::from_ne_bytes(bytes.try_into().unwrap())
+      }
+    }
+    impl Std430 for [$t; 2] {
+      const STD430_ALIGN: usize = 8;
+      const STD430_SIZE: usize = 8;
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        read_components(bytes)
+      }
+    }
+    impl Std430 for [$t; 3] {
+      const STD430_ALIGN: usize = 16;
+      const STD430_SIZE: usize = 12;
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        read_components(bytes)
+      }
+    }
+    impl Std430 for [$t; 4] {
+      const STD430_ALIGN: usize = 16;
+      const STD430_SIZE: usize = 16;
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        write_components(self, out);
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        read_components(bytes)
+      }
+    }
+  )*};
+}
+impl_std430_scalar!(f32, i32, u32);
+
+#[inline]
+fn write_components<T: Std430>(components: &[T], out: &mut [u8]) {
+  for (c, chunk) in components.iter().zip(out.chunks_exact_mut(4)) {
+    c.write_std430(chunk);
+  }
+}
+
+#[inline]
+fn read_components<T: Std430, const N: usize>(bytes: &[u8]) -> [T; N] {
+  core::array::from_fn(|i| T::read_std430(&bytes[(i * 4)..(i * 4 + 4)]))
+}
+
+// GLSL bools are 4 bytes in a buffer, and any non-zero value is true.
+impl Std430 for bool {
+  const STD430_ALIGN: usize = 4;
+  const STD430_SIZE: usize = 4;
+  #[inline]
+  fn write_std430(&self, out: &mut [u8]) {
+    u32::from(*self).write_std430(out)
+  }
+  #[inline]
+  fn read_std430(bytes: &[u8]) -> Self {
+    u32::read_std430(bytes) != 0
+  }
+}
+impl<const N: usize> Std430 for [bool; N]
+where
+  [u32; N]: Std430,
+{
+  const STD430_ALIGN: usize = <[u32; N]>::STD430_ALIGN;
+  const STD430_SIZE: usize = <[u32; N]>::STD430_SIZE;
+  #[inline]
+  fn write_std430(&self, out: &mut [u8]) {
+    self.map(u32::from).write_std430(out)
+  }
+  #[inline]
+  fn read_std430(bytes: &[u8]) -> Self {
+    <[u32; N]>::read_std430(bytes).map(|u| u != 0)
+  }
+}
+
+impl<T: Std430, const N: usize> Std430 for GlslArray<T, N> {
+  const STD430_ALIGN: usize = T::STD430_ALIGN;
+  const STD430_SIZE: usize = N * std430_array_stride::<T>();
+  #[inline]
+  fn write_std430(&self, out: &mut [u8]) {
+    let stride = std430_array_stride::<T>();
+    for (t, chunk) in self.0.iter().zip(out.chunks_mut(stride)) {
+      t.write_std430(&mut chunk[..T::STD430_SIZE]);
+    }
+  }
+  #[inline]
+  fn read_std430(bytes: &[u8]) -> Self {
+    let stride = std430_array_stride::<T>();
+    GlslArray(core::array::from_fn(|i| {
+      T::read_std430(&bytes[(i * stride)..(i * stride + T::STD430_SIZE)])
+    }))
+  }
+}
+
+// Matrices are laid out as an array of their column vectors.
+macro_rules! impl_std430_matrix {
+  ($($c:literal),*) => {$(
+    impl<const R: usize> Std430 for [[f32; R]; $c]
+    where
+      [f32; R]: Std430,
+    {
+      const STD430_ALIGN: usize = <[f32; R]>::STD430_ALIGN;
+      const STD430_SIZE: usize = $c * std430_array_stride::<[f32; R]>();
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        let stride = std430_array_stride::<[f32; R]>();
+        for (column, chunk) in self.iter().zip(out.chunks_mut(stride)) {
+          column.write_std430(&mut chunk[..<[f32; R]>::STD430_SIZE]);
+        }
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        GlslArray::<[f32; R], $c>::read_std430(bytes).0
+      }
+    }
+  )*};
+}
+impl_std430_matrix!(2, 3, 4);
+
+/// Implements `Std430` for a type by viewing it as another type with the same
+/// memory layout.
+#[allow(unused_macros)]
+macro_rules! impl_std430_as {
+  ($($t:ty => $as_t:ty),* $(,)?) => {$(
+    impl Std430 for $t {
+      const STD430_ALIGN: usize = <$as_t>::STD430_ALIGN;
+      const STD430_SIZE: usize = <$as_t>::STD430_SIZE;
+      #[inline]
+      fn write_std430(&self, out: &mut [u8]) {
+        const _: () = assert!(size_of::<$t>() == size_of::<$as_t>());
+        unsafe { &*(self as *const $t).cast::<$as_t>() }.write_std430(out)
+      }
+      #[inline]
+      fn read_std430(bytes: &[u8]) -> Self {
+        unsafe { core::mem::transmute_copy(&<$as_t>::read_std430(bytes)) }
+      }
+    }
+  )*};
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+  use super::*;
+  use mint::*;
+  impl_std430_as! {
+    Vector2<f32> => [f32; 2], Vector3<f32> => [f32; 3], Vector4<f32> => [f32; 4],
+    Vector2<i32> => [i32; 2], Vector3<i32> => [i32; 3], Vector4<i32> => [i32; 4],
+    Vector2<u32> => [u32; 2], Vector3<u32> => [u32; 3], Vector4<u32> => [u32; 4],
+    ColumnMatrix2<f32> => [[f32; 2]; 2],
+    ColumnMatrix3<f32> => [[f32; 3]; 3],
+    ColumnMatrix4<f32> => [[f32; 4]; 4],
+  }
+}
+
+#[cfg(feature = "ultraviolet")]
+mod ultraviolet_impls {
+  use super::*;
+  use ultraviolet::*;
+  impl_std430_as! {
+    Vec2 => [f32; 2], Vec3 => [f32; 3], Vec4 => [f32; 4],
+    Mat2 => [[f32; 2]; 2], Mat3 => [[f32; 3]; 3], Mat4 => [[f32; 4]; 4],
+  }
+}
+
+#[cfg(feature = "glam")]
+mod glam_impls {
+  use super::*;
+  use glam::*;
+  impl_std430_as! {
+    Vec2 => [f32; 2], Vec3 => [f32; 3], Vec4 => [f32; 4],
+    IVec2 => [i32; 2], IVec3 => [i32; 3], IVec4 => [i32; 4],
+    UVec2 => [u32; 2], UVec3 => [u32; 3], UVec4 => [u32; 4],
+    Mat2 => [[f32; 2]; 2], Mat3 => [[f32; 3]; 3], Mat4 => [[f32; 4]; 4],
+  }
+}
+
+impl EzGl {
+  /// Gets the index of a shader storage block by name.
+  ///
+  /// Requires GL 4.3 or GLES 3.1.
+  #[inline]
+  pub fn get_shader_storage_block_index(
+    &self, program: &ProgramObject, name: &str,
+  ) -> Option<GLuint> {
+    let name_z = format!("{name}\0");
+    let r = unsafe {
+      self.GetProgramResourceIndex(
+        program.0.get(),
+        GL_SHADER_STORAGE_BLOCK,
+        name_z.as_ptr().cast::<GLchar>(),
+      )
+    };
+    if r != GL_INVALID_INDEX {
+      Some(r)
+    } else {
+      None
+    }
+  }
+  /// Sets the shader storage buffer binding point that a shader storage block
+  /// reads from.
+  ///
+  /// Use [`bind_buffer_base`](Self::bind_buffer_base) or
+  /// [`bind_buffer_range`](Self::bind_buffer_range) with
+  /// [`ShaderStorageBuffer`](BufferTarget::ShaderStorageBuffer) to attach a
+  /// buffer to the binding point.
+  ///
+  /// This is not available in GLES, where the binding has to be set in the
+  /// shader with `layout(binding = N)`.
+  #[inline]
+  pub fn set_shader_storage_block_binding(
+    &self, program: &ProgramObject, block_index: GLuint, binding: GLuint,
+  ) {
+    unsafe { self.ShaderStorageBlockBinding(program.0.get(), block_index, binding) }
+  }
+
+  /// Allocates new storage for the buffer bound to `target`, holding a header
+  /// followed by a runtime-sized array with room for `capacity` elements.
+  ///
+  /// The header and `items` are written in, and the rest of the array is
+  /// zeroed.
+  ///
+  /// ## Panics
+  /// * If `items` is longer than `capacity`.
+  #[inline]
+  pub fn buffer_data_std430_with_array<H: Std430, T: Std430>(
+    &self, target: BufferTarget, header: &H, items: &[T], capacity: usize,
+    usage: BufferUsageHint,
+  ) {
+    assert!(items.len() <= capacity);
+    let array_offset = std430_trailing_array_offset::<H, T>();
+    let stride = std430_array_stride::<T>();
+    let mut bytes = vec![0; array_offset + capacity * stride];
+    header.write_std430(&mut bytes[..H::STD430_SIZE]);
+    for (t, chunk) in items.iter().zip(bytes[array_offset..].chunks_mut(stride)) {
+      t.write_std430(&mut chunk[..T::STD430_SIZE]);
+    }
+    self.buffer_data(target, &bytes, usage);
+  }
+
+  /// Reads one value from the buffer bound to `target`, starting at `offset`
+  /// bytes.
+  #[inline]
+  pub fn get_buffer_std430<T: Std430>(
+    &self, target: BufferTarget, offset: usize,
+  ) -> Result<T, ()> {
+    let mut bytes = vec![0; T::STD430_SIZE];
+    self.get_buffer_sub_data(target, offset, &mut bytes)?;
+    Ok(T::read_std430(&bytes))
+  }
+
+  /// Reads `count` array elements from the buffer bound to `target`, with the
+  /// first element starting at `offset` bytes.
+  #[inline]
+  pub fn get_buffer_std430_array<T: Std430>(
+    &self, target: BufferTarget, offset: usize, count: usize,
+  ) -> Result<Vec<T>, ()> {
+    let stride = std430_array_stride::<T>();
+    let mut bytes = vec![0; count * stride];
+    self.get_buffer_sub_data(target, offset, &mut bytes)?;
+    Ok(
+      bytes
+        .chunks_exact(stride)
+        .map(|chunk| T::read_std430(&chunk[..T::STD430_SIZE]))
+        .collect(),
+    )
+  }
+
+  /// Reads a header and the first `count` elements of the runtime-sized array
+  /// that follows it, from the buffer bound to `target`.
+  ///
+  /// If the header says how many elements there are (eg: an atomic counter
+  /// that a compute shader appends with), read the header alone first with
+  /// [`get_buffer_std430`](Self::get_buffer_std430), then the elements with
+  /// [`get_buffer_std430_array`](Self::get_buffer_std430_array) at
+  /// [`std430_trailing_array_offset`].
+  #[inline]
+  pub fn get_buffer_std430_with_array<H: Std430, T: Std430>(
+    &self, target: BufferTarget, count: usize,
+  ) -> Result<(H, Vec<T>), ()> {
+    let header = self.get_buffer_std430::<H>(target, 0)?;
+    let items = self.get_buffer_std430_array::<T>(
+      target,
+      std430_trailing_array_offset::<H, T>(),
+      count,
+    )?;
+    Ok((header, items))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+  }
+
+  #[test]
+  fn array_strides_are_not_rounded_to_16() {
+    assert_eq!(std430_array_stride::<f32>(), 4);
+    assert_eq!(std430_array_stride::<[f32; 2]>(), 8);
+    // vec3 still has the alignment of a vec4.
+    assert_eq!(std430_array_stride::<[f32; 3]>(), 16);
+    assert_eq!(<GlslArray<f32, 3>>::STD430_SIZE, 12);
+    let bytes = GlslArray([1.0f32, 2.0, 3.0]).to_std430_bytes();
+    assert_eq!((f32_at(&bytes, 4), f32_at(&bytes, 8)), (2.0, 3.0));
+  }
+
+  #[test]
+  fn matrix_layouts() {
+    // mat2 columns are 8 bytes apart, unlike std140.
+    assert_eq!((<[[f32; 2]; 2]>::STD430_ALIGN, <[[f32; 2]; 2]>::STD430_SIZE), (8, 16));
+    assert_eq!((<[[f32; 3]; 3]>::STD430_ALIGN, <[[f32; 3]; 3]>::STD430_SIZE), (16, 48));
+    let m = [[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+    let bytes = m.to_std430_bytes();
+    assert_eq!((f32_at(&bytes, 16), f32_at(&bytes, 32)), (4.0, 7.0));
+    assert_eq!(<[[f32; 3]; 3]>::read_std430(&bytes), m);
+  }
+
+  #[test]
+  fn struct_layout_is_not_rounded_to_16() {
+    // struct { float a; }
+    assert_eq!(std430_struct_layout(&[(4, 4)]), (4, 4));
+    // struct { float a; vec2 b; }
+    assert_eq!(std430_struct_layout(&[(4, 4), (8, 8)]), (8, 16));
+    // struct { vec3 a; float b; }
+    assert_eq!(std430_struct_layout(&[(16, 12), (4, 4)]), (16, 16));
+  }
+
+  #[test]
+  fn trailing_array_offset() {
+    assert_eq!(std430_trailing_array_offset::<f32, f32>(), 4);
+    assert_eq!(std430_trailing_array_offset::<f32, [f32; 4]>(), 16);
+    assert_eq!(std430_trailing_array_offset::<[u32; 3], [f32; 2]>(), 16);
+  }
+
+  /// The example block from the `std140` rules in the GL spec, laid out
+  /// with the `std430` rules instead.
+  #[cfg(feature = "derive")]
+  mod spec_example {
+    use super::*;
+    use crate::spec_example::{example, Example, F, O};
+
+    #[test]
+    fn sizes() {
+      assert_eq!((F::STD430_ALIGN, F::STD430_SIZE), (8, 16));
+      assert_eq!((O::STD430_ALIGN, O::STD430_SIZE), (16, 144));
+      assert_eq!((Example::STD430_ALIGN, Example::STD430_SIZE), (16, 384));
+    }
+
+    #[test]
+    fn offsets() {
+      let bytes = example().to_std430_bytes();
+      assert_eq!(f32_at(&bytes, 8), 2.0);
+      assert_eq!(f32_at(&bytes, 16), 4.0);
+      assert_eq!(&bytes[32..36], &7i32.to_ne_bytes());
+      assert_eq!(&bytes[40..44], &1u32.to_ne_bytes());
+      assert_eq!(f32_at(&bytes, 48), 8.0);
+      assert_eq!((f32_at(&bytes, 52), f32_at(&bytes, 56)), (9.0, 10.0));
+      assert_eq!((f32_at(&bytes, 64), f32_at(&bytes, 80)), (11.0, 14.0));
+      for (base, start) in [(100.0, 96), (200.0, 240)] {
+        assert_eq!(&bytes[start..start + 4], &1u32.to_ne_bytes());
+        assert_eq!(f32_at(&bytes, start + 16), base);
+        assert_eq!(f32_at(&bytes, start + 24), base + 2.0);
+        assert_eq!(f32_at(&bytes, start + 28), base + 3.0);
+        assert_eq!(f32_at(&bytes, start + 32), base + 4.0);
+        assert_eq!(f32_at(&bytes, start + 48), base + 6.0);
+        assert_eq!(f32_at(&bytes, start + 96), base + 7.0);
+      }
+    }
+
+    #[test]
+    fn read_round_trip() {
+      let bytes = example().to_std430_bytes();
+      assert_eq!(Example::read_std430(&bytes), example());
+    }
+  }
+}
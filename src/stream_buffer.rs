@@ -0,0 +1,365 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// How a [`StreamBuffer`] gets data into its buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamBufferMode {
+  /// Each write is a [`buffer_sub_data`](EzGl::buffer_sub_data) call.
+  ///
+  /// Works everywhere, but some drivers will still stall if the buffer is in
+  /// use.
+  BufferSubData,
+  /// Each write maps just the allocated range, unsynchronized, and copies the
+  /// data in. The stream buffer's fences are what make this safe.
+  MapUnsynchronized,
+  /// The buffer uses immutable storage that stays mapped (and coherent), so
+  /// each write is only a `memcpy`.
+  ///
+  /// Requires GL 4.4 or `ARB_buffer_storage`.
+  PersistentMapped,
+}
+
+#[derive(Debug)]
+enum StreamStorage {
+  Plain(BufferObject),
+  Persistent(PersistentMappedBuffer),
+}
+
+/// A ring allocator for data that's written fresh every frame.
+///
+/// Each write gets the next (aligned) span of the buffer, wrapping around to
+/// the start once it reaches the end. Calling [`end_frame`] after submitting a
+/// frame's draws puts a fence after them, and a later write that wraps around
+/// onto that frame's span will wait on its fence first. This way you never
+/// overwrite data the GPU might still be reading, and GL never has to sync
+/// implicitly.
+///
+/// * Make the buffer at least a few frames' worth of data long, or writes will
+///   end up waiting on the GPU.
+/// * Every method that writes binds the buffer to the stream buffer's target.
+///
+/// Made with [`create_stream_buffer`](EzGl::create_stream_buffer) and freed
+/// with [`delete_stream_buffer`](EzGl::delete_stream_buffer).
+///
+/// [`end_frame`]: StreamBuffer::end_frame
+#[derive(Debug)]
+pub struct StreamBuffer {
+  storage: StreamStorage,
+  mode: StreamBufferMode,
+  target: BufferTarget,
+  ring: StreamRing,
+  // The start position of each fenced frame, oldest first.
+  fences: VecDeque<(usize, SyncObject)>,
+}
+impl StreamBuffer {
+  /// The underlying buffer object, for binding.
+  #[inline]
+  #[must_use]
+  pub fn buffer(&self) -> &BufferObject {
+    match &self.storage {
+      StreamStorage::Plain(buffer) => buffer,
+      StreamStorage::Persistent(p) => p.buffer(),
+    }
+  }
+  #[inline]
+  #[must_use]
+  pub fn target(&self) -> BufferTarget {
+    self.target
+  }
+  #[inline]
+  #[must_use]
+  pub fn mode(&self) -> StreamBufferMode {
+    self.mode
+  }
+  /// The total size of the buffer, in bytes.
+  #[inline]
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.ring.len
+  }
+  /// Stream buffers are never empty, this is just for clippy.
+  #[inline]
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.ring.len == 0
+  }
+  /// The alignment that every allocation gets, at minimum.
+  ///
+  /// For the uniform and shader storage targets this is the GL offset
+  /// alignment for that target, otherwise it's 1.
+  #[inline]
+  #[must_use]
+  pub fn min_align(&self) -> usize {
+    self.ring.min_align
+  }
+
+  /// Writes the data into the next span of the buffer, returning the byte
+  /// offset that it was written to.
+  ///
+  /// * `align` must be a power of two. The offset will be aligned to at least
+  ///   this and also to [`min_align`](Self::min_align).
+  ///
+  /// If the span was used by an earlier frame this blocks until that frame's
+  /// fence is signaled.
+  ///
+  /// ## Failure
+  /// * `data` is empty, or bigger than the whole buffer.
+  /// * The write would overwrite data from the current frame (the buffer is
+  ///   too small for one frame's worth of data).
+  /// * Waiting on a fence or mapping the buffer failed.
+  #[inline]
+  pub fn write(&mut self, gl: &EzGl, data: &[u8], align: usize) -> Result<usize, ()> {
+    let offset = self.alloc(gl, data.len(), align)?;
+    let range = offset..(offset + data.len());
+    match &mut self.storage {
+      StreamStorage::Plain(buffer) => {
+        gl.bind_buffer(self.target, buffer);
+        if self.mode == StreamBufferMode::MapUnsynchronized {
+          // Safety: the buffer stays bound until the mapping is gone.
+          let mut mapping = unsafe {
+            gl.map_buffer_range(
+              self.target,
+              range,
+              MapAccess::WRITE | MapAccess::INVALIDATE_RANGE | MapAccess::UNSYNCHRONIZED,
+            )?
+          };
+          mapping.write(0, data)?;
+          mapping.unmap()?;
+        } else {
+          gl.buffer_sub_data(self.target, offset, data);
+        }
+      }
+      StreamStorage::Persistent(p) => {
+        gl.bind_buffer(self.target, p.buffer());
+        p.write_region(0, offset, data)?;
+      }
+    }
+    Ok(offset)
+  }
+  /// As [`write`](Self::write), but with any [`Pod`](bytemuck::Pod) slice.
+  ///
+  /// The alignment used is at least the alignment of `T`.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn write_pod<T: bytemuck::Pod>(
+    &mut self, gl: &EzGl, data: &[T], align: usize,
+  ) -> Result<usize, ()> {
+    self.write(gl, bytemuck::cast_slice(data), align.max(core::mem::align_of::<T>()))
+  }
+  /// Marks the end of a frame's worth of writes.
+  ///
+  /// Call this after issuing all of the draw (or other) commands that read the
+  /// frame's data. A fence goes into the command stream, and later writes that
+  /// wrap around onto this frame's data will wait for it.
+  ///
+  /// If nothing was written since the last call this does nothing.
+  #[inline]
+  pub fn end_frame(&mut self, gl: &EzGl) -> Result<(), ()> {
+    if self.ring.head != self.ring.frame_start {
+      let sync = gl.fence_sync()?;
+      self.fences.push_back((self.ring.frame_start, sync));
+      self.ring.frame_start = self.ring.head;
+    }
+    Ok(())
+  }
+
+  fn alloc(&mut self, gl: &EzGl, size: usize, align: usize) -> Result<usize, ()> {
+    let span = self.ring.next_span(size, align)?;
+    let overlap_limit = self.ring.overlap_limit(span.end);
+    while let Some(&(frame_start, _)) = self.fences.front() {
+      if frame_start >= overlap_limit {
+        break;
+      }
+      let (_, sync) = self.fences.pop_front().unwrap();
+      let wait_result = wait_for_sync(gl, &sync);
+      gl.delete_sync(sync);
+      wait_result?;
+    }
+    self.ring.head = span.end;
+    Ok(span.start % self.ring.len)
+  }
+}
+
+/// The allocation positions of a [`StreamBuffer`], kept apart from GL so that
+/// the arithmetic doesn't need a context to test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StreamRing {
+  len: usize,
+  min_align: usize,
+  // Positions are "virtual": they only ever increase, and the physical offset
+  // is the position modulo `len`. This makes it easy to tell which past frames
+  // a new allocation would overlap.
+  head: usize,
+  frame_start: usize,
+}
+impl StreamRing {
+  fn new(len: usize, min_align: usize) -> Self {
+    Self { len, min_align, head: 0, frame_start: 0 }
+  }
+  /// The virtual span that the next allocation would get, without taking it.
+  fn next_span(&self, size: usize, align: usize) -> Result<Range<usize>, ()> {
+    assert!(align.is_power_of_two());
+    if size == 0 || size > self.len {
+      return Err(());
+    }
+    let align = align.max(self.min_align);
+    let lap_start = self.head - (self.head % self.len);
+    let mut start = lap_start + align_up(self.head - lap_start, align);
+    if start + size > lap_start + self.len {
+      start = lap_start + self.len;
+    }
+    let end = start + size;
+    if self.frame_start < self.overlap_limit(end) {
+      return Err(());
+    }
+    Ok(start..end)
+  }
+  /// Everything written before this position shares physical bytes with a new
+  /// span ending at `end`, so frames starting before it must be retired.
+  fn overlap_limit(&self, end: usize) -> usize {
+    end.saturating_sub(self.len)
+  }
+}
+
+#[inline]
+fn align_up(x: usize, align: usize) -> usize {
+  (x + (align - 1)) & !(align - 1)
+}
+
+fn wait_for_sync(gl: &EzGl, sync: &SyncObject) -> Result<(), ()> {
+  const ONE_SECOND_NS: u64 = 1_000_000_000;
+  while !gl.client_wait_sync(sync, true, ONE_SECOND_NS)?.is_signaled() {}
+  Ok(())
+}
+
+impl EzGl {
+  /// Makes a new [`StreamBuffer`] of `len` bytes.
+  ///
+  /// The new buffer is left bound to `target`.
+  ///
+  /// ## Panics
+  /// * If `len` is 0.
+  #[inline]
+  pub fn create_stream_buffer(
+    &self, target: BufferTarget, len: usize, mode: StreamBufferMode,
+  ) -> Result<StreamBuffer, ()> {
+    assert!(len > 0);
+    let storage = match mode {
+      StreamBufferMode::BufferSubData | StreamBufferMode::MapUnsynchronized => {
+        let buffer = self.gen_buffer()?;
+        self.bind_buffer(target, &buffer);
+        self.buffer_data_uninit(target, len, BufferUsageHint::StreamDraw);
+        StreamStorage::Plain(buffer)
+      }
+      StreamBufferMode::PersistentMapped => {
+        StreamStorage::Persistent(self.create_persistent_mapped_buffer(
+          target,
+          len,
+          1,
+          BufferStorageFlags::MAP_WRITE
+            | BufferStorageFlags::MAP_PERSISTENT
+            | BufferStorageFlags::MAP_COHERENT,
+        )?)
+      }
+    };
+    let min_align = match target {
+      BufferTarget::UniformBuffer => self.get_uniform_buffer_offset_alignment(),
+      BufferTarget::ShaderStorageBuffer => {
+        self.get_shader_storage_buffer_offset_alignment()
+      }
+      _ => 1,
+    };
+    Ok(StreamBuffer {
+      storage,
+      mode,
+      target,
+      ring: StreamRing::new(len, min_align),
+      fences: VecDeque::new(),
+    })
+  }
+  /// Deletes a stream buffer along with any of its pending fences.
+  ///
+  /// This doesn't wait on the fences, so only call it once the GPU is done with
+  /// the buffer.
+  #[inline]
+  pub fn delete_stream_buffer(&self, stream: StreamBuffer) {
+    for (_, sync) in stream.fences {
+      self.delete_sync(sync);
+    }
+    match stream.storage {
+      StreamStorage::Plain(buffer) => self.delete_buffer(buffer),
+      StreamStorage::Persistent(p) => self.delete_persistent_mapped_buffer(p),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Takes the next span like `StreamBuffer::alloc` does, giving the physical
+  // offset along with the position before which frames had to be retired.
+  fn take(
+    ring: &mut StreamRing, size: usize, align: usize,
+  ) -> Result<(usize, usize), ()> {
+    let span = ring.next_span(size, align)?;
+    let overlap_limit = ring.overlap_limit(span.end);
+    ring.head = span.end;
+    Ok((span.start % ring.len, overlap_limit))
+  }
+
+  fn end_frame(ring: &mut StreamRing) {
+    ring.frame_start = ring.head;
+  }
+
+  #[test]
+  fn allocations_are_aligned() {
+    let mut ring = StreamRing::new(256, 16);
+    assert_eq!(take(&mut ring, 5, 1), Ok((0, 0)));
+    assert_eq!(take(&mut ring, 5, 1), Ok((16, 0)));
+    assert_eq!(take(&mut ring, 5, 64), Ok((64, 0)));
+  }
+
+  #[test]
+  fn wraps_to_the_start_and_retires_the_overlapped_frame() {
+    let mut ring = StreamRing::new(100, 1);
+    assert_eq!(take(&mut ring, 60, 1), Ok((0, 0)));
+    end_frame(&mut ring);
+    // Only 40 bytes are left in this lap, so the span moves to the next lap
+    // and overlaps the first frame (positions 0..60).
+    assert_eq!(take(&mut ring, 60, 1), Ok((0, 60)));
+    assert_eq!(ring.head, 160);
+  }
+
+  #[test]
+  fn exact_fit_at_the_end_of_a_lap() {
+    let mut ring = StreamRing::new(100, 1);
+    assert_eq!(take(&mut ring, 40, 1), Ok((0, 0)));
+    end_frame(&mut ring);
+    assert_eq!(take(&mut ring, 60, 1), Ok((40, 0)));
+    assert_eq!(ring.head, 100);
+    end_frame(&mut ring);
+    // The next lap starts right at the physical start of the buffer.
+    assert_eq!(take(&mut ring, 10, 1), Ok((0, 10)));
+  }
+
+  #[test]
+  fn current_frame_overlapping_itself_is_an_error() {
+    let mut ring = StreamRing::new(100, 1);
+    assert_eq!(take(&mut ring, 70, 1), Ok((0, 0)));
+    let before = ring.clone();
+    assert_eq!(take(&mut ring, 40, 1), Err(()));
+    assert_eq!(ring, before);
+    // Once the frame is fenced off, the same allocation is fine.
+    end_frame(&mut ring);
+    assert_eq!(take(&mut ring, 40, 1), Ok((0, 40)));
+  }
+
+  #[test]
+  fn bad_sizes_are_errors() {
+    let ring = StreamRing::new(100, 1);
+    assert_eq!(ring.next_span(0, 1), Err(()));
+    assert_eq!(ring.next_span(101, 1), Err(()));
+    assert_eq!(ring.next_span(100, 1), Ok(0..100));
+  }
+}
@@ -0,0 +1,372 @@
+use super::*;
+
+/// A vertex array that remembers its attribute layout and the size of its
+/// buffers, allowing for bounds checked (safe) draw calls.
+///
+/// The vertex array owns its buffers: vertex data goes in with
+/// [`add_vertex_buffer`](Self::add_vertex_buffer), and element data goes in
+/// with [`set_element_buffer`](Self::set_element_buffer). Element data is
+/// scanned during upload, so that draws can check that no element refers to a
+/// vertex past the end of the vertex data.
+///
+/// The checks are only correct if the vertex array's attributes and buffers
+/// are changed exclusively through this type. To make sure of that, the
+/// buffers are private, and every method that binds the vertex array (or a
+/// buffer to [`ArrayBuffer`](BufferTarget::ArrayBuffer)) clears that binding
+/// again before returning.
+///
+/// Made with [`gen_tracked_vertex_array`](EzGl::gen_tracked_vertex_array) and
+/// freed with [`delete_tracked_vertex_array`](EzGl::delete_tracked_vertex_array).
+#[derive(Debug)]
+pub struct TrackedVertexArray {
+  vao: VertexArrayObject,
+  vertex_buffers: Vec<TrackedVertexBuffer>,
+  element_buffer: Option<TrackedElementBuffer>,
+}
+
+#[derive(Debug)]
+struct TrackedVertexBuffer {
+  buffer: BufferObject,
+  len: usize,
+  stride: usize,
+  attributes: Vec<TrackedAttribute>,
+}
+
+#[derive(Debug)]
+struct TrackedAttribute {
+  location: GLuint,
+  offset: usize,
+  /// The number of bytes that one vertex of the attribute reads.
+  byte_size: usize,
+}
+
+#[derive(Debug)]
+struct TrackedElementBuffer {
+  buffer: BufferObject,
+  len: usize,
+  ty: GLenum,
+  element_size: usize,
+  max_value: Option<u32>,
+}
+
+impl TrackedVertexArray {
+  /// The inner vertex array.
+  ///
+  /// ## Safety
+  /// * While the vertex array is bound, you must not change its attributes
+  ///   (eg: with [`vertex_attrib_f32_pointer`](EzGl::vertex_attrib_f32_pointer)
+  ///   or [`enable_vertex_attrib_array`](EzGl::enable_vertex_attrib_array)),
+  ///   or bind another buffer to
+  ///   [`ElementArrayBuffer`](BufferTarget::ElementArrayBuffer). The checked
+  ///   draws would then read out of bounds.
+  #[inline]
+  #[must_use]
+  pub unsafe fn vao(&self) -> &VertexArrayObject {
+    &self.vao
+  }
+
+  /// The number of vertices that every enabled attribute can read without
+  /// going out of bounds.
+  ///
+  /// If there are no attributes this is `usize::MAX`.
+  #[inline]
+  #[must_use]
+  pub fn vertex_count(&self) -> usize {
+    self
+      .vertex_buffers
+      .iter()
+      .flat_map(|vb| {
+        vb.attributes.iter().map(|attr| {
+          let end_of_first = attr.offset + attr.byte_size;
+          if vb.len < end_of_first {
+            0
+          } else {
+            (vb.len - end_of_first) / vb.stride + 1
+          }
+        })
+      })
+      .min()
+      .unwrap_or(usize::MAX)
+  }
+
+  /// The number of elements in the element buffer, if any.
+  #[inline]
+  #[must_use]
+  pub fn element_count(&self) -> Option<usize> {
+    self.element_buffer.as_ref().map(|eb| eb.len / eb.element_size)
+  }
+
+  /// Makes a new vertex buffer holding `data`, and configures the attributes
+  /// of `V` to read from it. Returns the index of the vertex buffer, for use
+  /// with [`update_vertex_buffer`](Self::update_vertex_buffer).
+  ///
+  /// `data` should be a slice of `V` values viewed as bytes. If any attribute
+  /// locations of `V` were previously used by another vertex buffer, they now
+  /// read from this buffer instead.
+  ///
+  /// This clears the vertex array binding and the
+  /// [`ArrayBuffer`](BufferTarget::ArrayBuffer) binding.
+  ///
+  /// ## Failure
+  /// * `V` is zero sized, or any of its attributes has an unknown component
+  ///   type, a component count outside of 1 to 4, or doesn't fit within `V`.
+  /// * GL couldn't make a new buffer.
+  #[inline]
+  pub fn add_vertex_buffer<V: Vertex>(
+    &mut self, gl: &EzGl, data: &[u8], usage: BufferUsageHint,
+  ) -> Result<usize, ()> {
+    let stride = size_of::<V>();
+    if stride == 0 {
+      return Err(());
+    }
+    let attributes = V::ATTRIBUTES
+      .iter()
+      .map(|attr| {
+        let byte_size = vertex_attribute_byte_size(attr)?;
+        if attr.offset.checked_add(byte_size).is_some_and(|end| end <= stride) {
+          Ok(TrackedAttribute { location: attr.location, offset: attr.offset, byte_size })
+        } else {
+          Err(())
+        }
+      })
+      .collect::<Result<Vec<_>, ()>>()?;
+    let buffer = gl.gen_buffer()?;
+    gl.bind_vertex_array(&self.vao);
+    gl.bind_buffer(BufferTarget::ArrayBuffer, &buffer);
+    gl.buffer_data(BufferTarget::ArrayBuffer, data, usage);
+    gl.apply_vertex_layout::<V>();
+    gl.clear_vertex_array_binding();
+    gl.clear_buffer_binding(BufferTarget::ArrayBuffer);
+    for vb in self.vertex_buffers.iter_mut() {
+      vb.attributes
+        .retain(|old| V::ATTRIBUTES.iter().all(|new| new.location != old.location));
+    }
+    self.vertex_buffers.push(TrackedVertexBuffer {
+      buffer,
+      len: data.len(),
+      stride,
+      attributes,
+    });
+    Ok(self.vertex_buffers.len() - 1)
+  }
+
+  /// As [`add_vertex_buffer`](Self::add_vertex_buffer), but with a slice of
+  /// vertices.
+  #[inline]
+  #[cfg(feature = "bytemuck")]
+  pub fn add_vertex_buffer_pod<V: Vertex + bytemuck::Pod>(
+    &mut self, gl: &EzGl, data: &[V], usage: BufferUsageHint,
+  ) -> Result<usize, ()> {
+    self.add_vertex_buffer::<V>(gl, bytemuck::cast_slice(data), usage)
+  }
+
+  /// Replaces the data of a vertex buffer, reallocating its storage.
+  ///
+  /// This clears the [`ArrayBuffer`](BufferTarget::ArrayBuffer) binding.
+  ///
+  /// ## Panics
+  /// * If `index` isn't a vertex buffer of this vertex array.
+  #[inline]
+  pub fn update_vertex_buffer(
+    &mut self, gl: &EzGl, index: usize, data: &[u8], usage: BufferUsageHint,
+  ) {
+    let vb = &mut self.vertex_buffers[index];
+    gl.bind_buffer(BufferTarget::ArrayBuffer, &vb.buffer);
+    gl.buffer_data(BufferTarget::ArrayBuffer, data, usage);
+    gl.clear_buffer_binding(BufferTarget::ArrayBuffer);
+    vb.len = data.len();
+  }
+
+  /// Sets the element data, making the element buffer if necessary.
+  ///
+  /// The elements are scanned for their maximum value, which the checked
+  /// element draws compare against the vertex count.
+  ///
+  /// This clears the vertex array binding.
+  ///
+  /// ## Failure
+  /// * GL couldn't make the element buffer.
+  #[inline]
+  pub fn set_element_buffer<T: DrawElementsType + Copy + Into<u32>>(
+    &mut self, gl: &EzGl, data: &[T], usage: BufferUsageHint,
+  ) -> Result<(), ()> {
+    // Make the buffer before binding the vertex array, so that a failure
+    // can't return with the vertex array still bound.
+    let eb = match self.element_buffer.as_mut() {
+      Some(eb) => eb,
+      None => self.element_buffer.insert(TrackedElementBuffer {
+        buffer: gl.gen_buffer()?,
+        len: 0,
+        ty: T::TY,
+        element_size: size_of::<T>(),
+        max_value: None,
+      }),
+    };
+    gl.bind_vertex_array(&self.vao);
+    gl.bind_buffer(BufferTarget::ElementArrayBuffer, &eb.buffer);
+    // Safety: all element types are plain integers.
+    let bytes = unsafe {
+      slice_from_raw_parts(data.as_ptr().cast::<u8>(), core::mem::size_of_val(data))
+    };
+    gl.buffer_data(BufferTarget::ElementArrayBuffer, bytes, usage);
+    gl.clear_vertex_array_binding();
+    eb.len = bytes.len();
+    eb.ty = T::TY;
+    eb.element_size = size_of::<T>();
+    eb.max_value = data.iter().map(|&t| t.into()).max();
+    Ok(())
+  }
+}
+
+/// The number of bytes that one vertex of an attribute reads, or an error if
+/// the attribute's type or component count isn't valid.
+fn vertex_attribute_byte_size(attr: &VertexAttribute) -> Result<usize, ()> {
+  if !(1..=4).contains(&attr.size) {
+    return Err(());
+  }
+  let component_size = match attr.ty {
+    GL_INT_2_10_10_10_REV | GL_UNSIGNED_INT_2_10_10_10_REV => return Ok(4),
+    GL_BYTE | GL_UNSIGNED_BYTE => 1,
+    GL_SHORT | GL_UNSIGNED_SHORT | GL_HALF_FLOAT => 2,
+    GL_INT | GL_UNSIGNED_INT | GL_FLOAT => 4,
+    GL_DOUBLE => 8,
+    _ => return Err(()),
+  };
+  Ok(component_size * attr.size as usize)
+}
+
+impl EzGl {
+  #[inline]
+  pub fn gen_tracked_vertex_array(&self) -> Result<TrackedVertexArray, ()> {
+    Ok(TrackedVertexArray {
+      vao: self.gen_vertex_array()?,
+      vertex_buffers: Vec::new(),
+      element_buffer: None,
+    })
+  }
+
+  /// Deletes the vertex array, along with all of its buffers.
+  #[inline]
+  pub fn delete_tracked_vertex_array(&self, vao: TrackedVertexArray) {
+    for vb in vao.vertex_buffers {
+      self.delete_buffer(vb.buffer);
+    }
+    if let Some(eb) = vao.element_buffer {
+      self.delete_buffer(eb.buffer);
+    }
+    self.delete_vertex_array(vao.vao);
+  }
+
+  /// Binds the vertex array and draws using its vertex data, after checking
+  /// that the draw stays in bounds. The vertex array binding is cleared
+  /// afterwards.
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of vertices to draw.
+  ///
+  /// ## Failure
+  /// * The range is empty, or goes past
+  ///   [`vertex_count`](TrackedVertexArray::vertex_count).
+  #[inline]
+  pub fn draw_arrays_checked(
+    &self, vao: &TrackedVertexArray, mode: DrawMode, range: Range<usize>,
+  ) -> Result<(), ()> {
+    if range.start >= range.end || range.end > vao.vertex_count() {
+      return Err(());
+    }
+    self.bind_vertex_array(&vao.vao);
+    unsafe { self.draw_arrays(mode, range) };
+    self.clear_vertex_array_binding();
+    Ok(())
+  }
+
+  /// Binds the vertex array and draws using its element data, after checking
+  /// that the draw stays in bounds. The vertex array binding is cleared
+  /// afterwards.
+  ///
+  /// * `mode` is the geometric primitive to assemble.
+  /// * `range` is the `low..high` index span of elements to draw.
+  ///
+  /// ## Failure
+  /// * The vertex array has no element buffer.
+  /// * The range is empty, or goes past
+  ///   [`element_count`](TrackedVertexArray::element_count).
+  /// * Any element in the buffer is past
+  ///   [`vertex_count`](TrackedVertexArray::vertex_count). This uses the
+  ///   maximum element of the *whole* buffer, not just `range`.
+  #[inline]
+  pub fn draw_elements_checked(
+    &self, vao: &TrackedVertexArray, mode: DrawMode, range: Range<usize>,
+  ) -> Result<(), ()> {
+    let eb = vao.element_buffer.as_ref().ok_or(())?;
+    if range.start >= range.end || range.end > eb.len / eb.element_size {
+      return Err(());
+    }
+    if let Some(max_value) = eb.max_value {
+      if usize::try_from(max_value).unwrap() >= vao.vertex_count() {
+        return Err(());
+      }
+    }
+    self.bind_vertex_array(&vao.vao);
+    let count = range.end - range.start;
+    unsafe {
+      self.DrawElements(
+        mode as GLenum,
+        count.try_into().unwrap(),
+        eb.ty,
+        (range.start * eb.element_size) as *const c_void,
+      )
+    };
+    self.clear_vertex_array_binding();
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use core::sync::atomic::{AtomicU32, Ordering};
+
+  static BOUND_VAO: AtomicU32 = AtomicU32::new(0);
+
+  unsafe extern "system" fn gen_vertex_arrays(n: GLsizei, arrays: *mut GLuint) {
+    assert_eq!(n, 1);
+    unsafe { *arrays = 1 };
+  }
+  unsafe extern "system" fn gen_no_buffers(n: GLsizei, buffers: *mut GLuint) {
+    assert_eq!(n, 1);
+    unsafe { *buffers = 0 };
+  }
+  unsafe extern "system" fn bind_vertex_array(array: GLuint) {
+    BOUND_VAO.store(array, Ordering::SeqCst);
+  }
+
+  /// A GL where making a vertex array works but making a buffer fails.
+  fn gl_without_buffers() -> Box<EzGl> {
+    let mut gl = EzGl::new_boxed();
+    unsafe {
+      gl.load(|name| {
+        let name = core::ffi::CStr::from_ptr(name.cast()).to_str().unwrap();
+        match name {
+          "glGenVertexArrays" => gen_vertex_arrays as *const c_void,
+          "glGenBuffers" => gen_no_buffers as *const c_void,
+          "glBindVertexArray" => bind_vertex_array as *const c_void,
+          _ => core::ptr::null(),
+        }
+      })
+    };
+    gl
+  }
+
+  #[test]
+  fn failed_element_buffer_leaves_no_vertex_array_bound() {
+    let gl = gl_without_buffers();
+    let mut vao = gl.gen_tracked_vertex_array().unwrap();
+    assert_eq!(
+      vao.set_element_buffer::<u16>(&gl, &[0, 1, 2], BufferUsageHint::StaticDraw),
+      Err(())
+    );
+    assert_eq!(BOUND_VAO.load(Ordering::SeqCst), 0);
+    assert_eq!(vao.element_count(), None);
+  }
+}
@@ -0,0 +1,365 @@
+use super::*;
+use std::collections::HashMap;
+
+/// Values that can be assigned to a uniform with
+/// [`set_uniform`](EzGl::set_uniform).
+///
+/// * `f32`, `i32`, `u32`, and `bool` are the GLSL scalars of those types.
+/// * `[T; N]` of those is the matching `vecN` (`ivecN`, `uvecN`, `bvecN`).
+/// * `[[f32; R]; C]` is a column-major matrix of `C` columns and `R` rows,
+///   which is `matC` (or `matCxR` when not square).
+/// * Slices of any of the above set a uniform array, starting at the location
+///   given.
+///
+/// With the `mint`, `ultraviolet`, and `glam` features the vector and matrix
+/// types of those crates can also be used.
+pub trait Uniform {
+  /// Sets the uniform in the current program.
+  fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation);
+  /// Sets the uniform in the given program, which doesn't have to be current.
+  fn set_program_uniform(&self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation);
+}
+
+impl EzGl {
+  /// Sets the value of a uniform in the current program.
+  ///
+  /// ```no_run
+  /// # let gl = ezgl::EzGl::new_boxed();
+  /// # let program = gl.create_program().unwrap();
+  /// let loc = gl.get_uniform_location(&program, "light_dir").unwrap();
+  /// gl.set_uniform(loc, &[0.0_f32, -1.0, 0.0]);
+  /// ```
+  #[inline]
+  pub fn set_uniform<U: Uniform + ?Sized>(&self, loc: ShaderLocation, value: &U) {
+    value.set_uniform(self, loc)
+  }
+  /// Sets the value of a uniform in any program, which doesn't have to be the
+  /// current program.
+  ///
+  /// Requires GL 4.1 or GLES 3.1.
+  #[inline]
+  pub fn set_program_uniform<U: Uniform + ?Sized>(
+    &self, program: &ProgramObject, loc: ShaderLocation, value: &U,
+  ) {
+    value.set_program_uniform(self, program, loc)
+  }
+}
+
+/// Implements `Uniform` for a type and for slices of that type, using one of
+/// the `glUniform*v` and `glProgramUniform*v` functions.
+///
+/// The type must have the exact layout of the components that the GL function
+/// reads.
+macro_rules! impl_uniform_v {
+  ($t:ty, $f:ident, $pf:ident) => {
+    impl Uniform for $t {
+      #[inline]
+      fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+        unsafe { gl.$f(loc.0, 1, (self as *const $t).cast()) }
+      }
+      #[inline]
+      fn set_program_uniform(
+        &self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation,
+      ) {
+        unsafe { gl.$pf(program.0.get(), loc.0, 1, (self as *const $t).cast()) }
+      }
+    }
+    impl Uniform for [$t] {
+      #[inline]
+      fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+        unsafe { gl.$f(loc.0, self.len().try_into().unwrap(), self.as_ptr().cast()) }
+      }
+      #[inline]
+      fn set_program_uniform(
+        &self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation,
+      ) {
+        unsafe {
+          gl.$pf(
+            program.0.get(),
+            loc.0,
+            self.len().try_into().unwrap(),
+            self.as_ptr().cast(),
+          )
+        }
+      }
+    }
+  };
+  (matrix $t:ty, $f:ident, $pf:ident) => {
+    impl Uniform for $t {
+      #[inline]
+      fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+        unsafe { gl.$f(loc.0, 1, GLboolean::FALSE, (self as *const $t).cast()) }
+      }
+      #[inline]
+      fn set_program_uniform(
+        &self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation,
+      ) {
+        unsafe {
+          gl.$pf(program.0.get(), loc.0, 1, GLboolean::FALSE, (self as *const $t).cast())
+        }
+      }
+    }
+    impl Uniform for [$t] {
+      #[inline]
+      fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+        unsafe {
+          gl.$f(
+            loc.0,
+            self.len().try_into().unwrap(),
+            GLboolean::FALSE,
+            self.as_ptr().cast(),
+          )
+        }
+      }
+      #[inline]
+      fn set_program_uniform(
+        &self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation,
+      ) {
+        unsafe {
+          gl.$pf(
+            program.0.get(),
+            loc.0,
+            self.len().try_into().unwrap(),
+            GLboolean::FALSE,
+            self.as_ptr().cast(),
+          )
+        }
+      }
+    }
+  };
+}
+
+impl_uniform_v!(f32, Uniform1fv, ProgramUniform1fv);
+impl_uniform_v!([f32; 2], Uniform2fv, ProgramUniform2fv);
+impl_uniform_v!([f32; 3], Uniform3fv, ProgramUniform3fv);
+impl_uniform_v!([f32; 4], Uniform4fv, ProgramUniform4fv);
+impl_uniform_v!(i32, Uniform1iv, ProgramUniform1iv);
+impl_uniform_v!([i32; 2], Uniform2iv, ProgramUniform2iv);
+impl_uniform_v!([i32; 3], Uniform3iv, ProgramUniform3iv);
+impl_uniform_v!([i32; 4], Uniform4iv, ProgramUniform4iv);
+impl_uniform_v!(u32, Uniform1uiv, ProgramUniform1uiv);
+impl_uniform_v!([u32; 2], Uniform2uiv, ProgramUniform2uiv);
+impl_uniform_v!([u32; 3], Uniform3uiv, ProgramUniform3uiv);
+impl_uniform_v!([u32; 4], Uniform4uiv, ProgramUniform4uiv);
+impl_uniform_v!(matrix [[f32; 2]; 2], UniformMatrix2fv, ProgramUniformMatrix2fv);
+impl_uniform_v!(matrix [[f32; 3]; 3], UniformMatrix3fv, ProgramUniformMatrix3fv);
+impl_uniform_v!(matrix [[f32; 4]; 4], UniformMatrix4fv, ProgramUniformMatrix4fv);
+impl_uniform_v!(matrix [[f32; 3]; 2], UniformMatrix2x3fv, ProgramUniformMatrix2x3fv);
+impl_uniform_v!(matrix [[f32; 4]; 2], UniformMatrix2x4fv, ProgramUniformMatrix2x4fv);
+impl_uniform_v!(matrix [[f32; 2]; 3], UniformMatrix3x2fv, ProgramUniformMatrix3x2fv);
+impl_uniform_v!(matrix [[f32; 4]; 3], UniformMatrix3x4fv, ProgramUniformMatrix3x4fv);
+impl_uniform_v!(matrix [[f32; 2]; 4], UniformMatrix4x2fv, ProgramUniformMatrix4x2fv);
+impl_uniform_v!(matrix [[f32; 3]; 4], UniformMatrix4x3fv, ProgramUniformMatrix4x3fv);
+
+// GL takes bools as ints (or floats), so these have to convert the data.
+impl Uniform for bool {
+  #[inline]
+  fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+    gl.set_uniform_bool(loc, *self)
+  }
+  #[inline]
+  fn set_program_uniform(&self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation) {
+    i32::from(*self).set_program_uniform(gl, program, loc)
+  }
+}
+impl<const N: usize> Uniform for [bool; N]
+where
+  [i32; N]: Uniform,
+{
+  #[inline]
+  fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+    self.map(i32::from).set_uniform(gl, loc)
+  }
+  #[inline]
+  fn set_program_uniform(&self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation) {
+    self.map(i32::from).set_program_uniform(gl, program, loc)
+  }
+}
+impl Uniform for [bool] {
+  #[inline]
+  fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+    let ints: Vec<i32> = self.iter().copied().map(i32::from).collect();
+    ints.set_uniform(gl, loc)
+  }
+  #[inline]
+  fn set_program_uniform(&self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation) {
+    let ints: Vec<i32> = self.iter().copied().map(i32::from).collect();
+    ints.set_program_uniform(gl, program, loc)
+  }
+}
+impl<const N: usize> Uniform for [[bool; N]]
+where
+  [[i32; N]]: Uniform,
+{
+  #[inline]
+  fn set_uniform(&self, gl: &EzGl, loc: ShaderLocation) {
+    let ints: Vec<[i32; N]> = self.iter().map(|b| b.map(i32::from)).collect();
+    ints.set_uniform(gl, loc)
+  }
+  #[inline]
+  fn set_program_uniform(&self, gl: &EzGl, program: &ProgramObject, loc: ShaderLocation) {
+    let ints: Vec<[i32; N]> = self.iter().map(|b| b.map(i32::from)).collect();
+    ints.set_program_uniform(gl, program, loc)
+  }
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+  use super::*;
+  use mint::*;
+  impl_uniform_v!(Vector2<f32>, Uniform2fv, ProgramUniform2fv);
+  impl_uniform_v!(Vector3<f32>, Uniform3fv, ProgramUniform3fv);
+  impl_uniform_v!(Vector4<f32>, Uniform4fv, ProgramUniform4fv);
+  impl_uniform_v!(Vector2<i32>, Uniform2iv, ProgramUniform2iv);
+  impl_uniform_v!(Vector3<i32>, Uniform3iv, ProgramUniform3iv);
+  impl_uniform_v!(Vector4<i32>, Uniform4iv, ProgramUniform4iv);
+  impl_uniform_v!(Vector2<u32>, Uniform2uiv, ProgramUniform2uiv);
+  impl_uniform_v!(Vector3<u32>, Uniform3uiv, ProgramUniform3uiv);
+  impl_uniform_v!(Vector4<u32>, Uniform4uiv, ProgramUniform4uiv);
+  // mint names matrices as "rows x columns", GL names them "columns x rows".
+  impl_uniform_v!(matrix ColumnMatrix2<f32>, UniformMatrix2fv, ProgramUniformMatrix2fv);
+  impl_uniform_v!(matrix ColumnMatrix3<f32>, UniformMatrix3fv, ProgramUniformMatrix3fv);
+  impl_uniform_v!(matrix ColumnMatrix4<f32>, UniformMatrix4fv, ProgramUniformMatrix4fv);
+  impl_uniform_v!(matrix ColumnMatrix3x2<f32>, UniformMatrix2x3fv, ProgramUniformMatrix2x3fv);
+  impl_uniform_v!(matrix ColumnMatrix4x2<f32>, UniformMatrix2x4fv, ProgramUniformMatrix2x4fv);
+  impl_uniform_v!(matrix ColumnMatrix2x3<f32>, UniformMatrix3x2fv, ProgramUniformMatrix3x2fv);
+  impl_uniform_v!(matrix ColumnMatrix4x3<f32>, UniformMatrix3x4fv, ProgramUniformMatrix3x4fv);
+  impl_uniform_v!(matrix ColumnMatrix2x4<f32>, UniformMatrix4x2fv, ProgramUniformMatrix4x2fv);
+  impl_uniform_v!(matrix ColumnMatrix3x4<f32>, UniformMatrix4x3fv, ProgramUniformMatrix4x3fv);
+}
+
+#[cfg(feature = "ultraviolet")]
+mod ultraviolet_impls {
+  use super::*;
+  use ultraviolet::*;
+  impl_uniform_v!(Vec2, Uniform2fv, ProgramUniform2fv);
+  impl_uniform_v!(Vec3, Uniform3fv, ProgramUniform3fv);
+  impl_uniform_v!(Vec4, Uniform4fv, ProgramUniform4fv);
+  impl_uniform_v!(matrix Mat2, UniformMatrix2fv, ProgramUniformMatrix2fv);
+  impl_uniform_v!(matrix Mat3, UniformMatrix3fv, ProgramUniformMatrix3fv);
+  impl_uniform_v!(matrix Mat4, UniformMatrix4fv, ProgramUniformMatrix4fv);
+}
+
+#[cfg(feature = "glam")]
+mod glam_impls {
+  use super::*;
+  use glam::*;
+  // `Vec3A` and `Mat3A` are padded to 16 bytes per vector, so they can't be
+  // used directly.
+  impl_uniform_v!(Vec2, Uniform2fv, ProgramUniform2fv);
+  impl_uniform_v!(Vec3, Uniform3fv, ProgramUniform3fv);
+  impl_uniform_v!(Vec4, Uniform4fv, ProgramUniform4fv);
+  impl_uniform_v!(IVec2, Uniform2iv, ProgramUniform2iv);
+  impl_uniform_v!(IVec3, Uniform3iv, ProgramUniform3iv);
+  impl_uniform_v!(IVec4, Uniform4iv, ProgramUniform4iv);
+  impl_uniform_v!(UVec2, Uniform2uiv, ProgramUniform2uiv);
+  impl_uniform_v!(UVec3, Uniform3uiv, ProgramUniform3uiv);
+  impl_uniform_v!(UVec4, Uniform4uiv, ProgramUniform4uiv);
+  impl_uniform_v!(matrix Mat2, UniformMatrix2fv, ProgramUniformMatrix2fv);
+  impl_uniform_v!(matrix Mat3, UniformMatrix3fv, ProgramUniformMatrix3fv);
+  impl_uniform_v!(matrix Mat4, UniformMatrix4fv, ProgramUniformMatrix4fv);
+}
+
+/// A program along with the locations of all of its active uniforms.
+///
+/// The uniforms are queried once when this is made, so that setting a
+/// uniform by name doesn't need to allocate or call into the driver for the
+/// lookup.
+///
+/// Made with [`cache_uniform_locations`](EzGl::cache_uniform_locations) and
+/// freed with [`delete_cached_program`](EzGl::delete_cached_program).
+#[derive(Debug)]
+pub struct CachedProgram {
+  program: ProgramObject,
+  uniforms: HashMap<Box<str>, ShaderLocation>,
+}
+impl CachedProgram {
+  #[inline]
+  #[must_use]
+  pub fn program(&self) -> &ProgramObject {
+    &self.program
+  }
+  /// Looks up the location of a uniform.
+  ///
+  /// Array uniforms can be found by their plain name (`lights`), the name of
+  /// their first element (`lights[0]`), or the name of any other element
+  /// (`lights[3]`).
+  #[inline]
+  #[must_use]
+  pub fn uniform_location(&self, name: &str) -> Option<ShaderLocation> {
+    self.uniforms.get(name).copied()
+  }
+  /// All names that [`uniform_location`](Self::uniform_location) knows about.
+  #[inline]
+  pub fn uniform_names(&self) -> impl Iterator<Item = &str> {
+    self.uniforms.keys().map(|name| &**name)
+  }
+}
+
+impl EzGl {
+  /// Queries the locations of all active uniforms of a linked program.
+  ///
+  /// Uniforms within uniform blocks don't have a location, so they're not
+  /// included.
+  #[inline]
+  pub fn cache_uniform_locations(&self, program: ProgramObject) -> CachedProgram {
+    let mut uniforms = HashMap::new();
+    for uniform in self.get_active_uniforms(&program) {
+      let Some(loc) = uniform.location else {
+        continue;
+      };
+      // Arrays are reported with the name of their first element.
+      if let Some(base) = uniform.name.strip_suffix("[0]") {
+        uniforms.insert(Box::from(base), loc);
+        for i in 1..uniform.array_size {
+          let element = format!("{base}[{i}]");
+          if let Some(loc) = self.get_uniform_location(&program, &element) {
+            uniforms.insert(element.into_boxed_str(), loc);
+          }
+        }
+      }
+      uniforms.insert(uniform.name, loc);
+    }
+    CachedProgram { program, uniforms }
+  }
+
+  #[inline]
+  pub fn delete_cached_program(&self, program: CachedProgram) {
+    self.delete_program(program.program)
+  }
+
+  /// Sets the value of a uniform in the current program, by name.
+  ///
+  /// The program must be the current program.
+  ///
+  /// ## Failure
+  /// * If the program has no active uniform with that name. In debug builds
+  ///   this also prints a warning to stderr, since it's usually a typo or a
+  ///   uniform that the compiler optimized out.
+  #[inline]
+  pub fn set_uniform_by_name<U: Uniform + ?Sized>(
+    &self, program: &CachedProgram, name: &str, value: &U,
+  ) -> Result<(), ()> {
+    let loc = lookup_uniform(program, name)?;
+    Ok(self.set_uniform(loc, value))
+  }
+  /// As [`set_uniform_by_name`](Self::set_uniform_by_name), but the program
+  /// doesn't have to be the current program.
+  ///
+  /// Requires GL 4.1 or GLES 3.1.
+  #[inline]
+  pub fn set_program_uniform_by_name<U: Uniform + ?Sized>(
+    &self, program: &CachedProgram, name: &str, value: &U,
+  ) -> Result<(), ()> {
+    let loc = lookup_uniform(program, name)?;
+    Ok(self.set_program_uniform(&program.program, loc, value))
+  }
+}
+
+fn lookup_uniform(program: &CachedProgram, name: &str) -> Result<ShaderLocation, ()> {
+  program.uniform_location(name).ok_or_else(|| {
+    if cfg!(debug_assertions) {
+      eprintln!("Program {} has no active uniform named `{name}`", program.program.0);
+    }
+  })
+}